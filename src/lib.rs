@@ -1,407 +1,6155 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
 /// This lib allows to compute price after tax of an item
 /// and a basket of items.
-use std::str::FromStr;
+///
+/// Without the default `std` feature only the numeric core is available:
+/// [`Money`], [`Prices`], the [`Tax`] trait, [`round_tax`] and
+/// [`round_numbers`], which then needs the `libm` feature for floating point
+/// rounding.
+use core::iter::Sum;
+use core::ops::{Add, Mul, Sub};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::hash::{Hash, Hasher};
 
-#[derive(Debug)]
-enum Imported {
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("either the `std` or the `libm` feature must be enabled");
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An amount of money, stored as an exact number of cents. Prices and totals
+/// can be compared with `==`, no tolerance is needed.
+///
+/// The operators overflow like `i64` arithmetic, panicking in debug builds;
+/// [`Money::checked_mul`] reports it instead. Items keep their line totals
+/// far enough from the limits for the receipt computations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub fn from_cents(cents: i64) -> Self {
+        Self(cents)
+    }
+
+    pub fn cents(&self) -> i64 {
+        self.0
+    }
+
+    /// Multiplies by a quantity, returning `None` on overflow.
+    pub fn checked_mul(self, quantity: i32) -> Option<Money> {
+        self.0.checked_mul(i64::from(quantity)).map(Money)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+
+impl Mul<i32> for Money {
+    type Output = Money;
+    fn mul(self, quantity: i32) -> Money {
+        Money(self.0 * i64::from(quantity))
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.format_with(&NumberFormat::default()))
+    }
+}
+
+/// The separators used to print amounts of money. The default is the
+/// US-style "1234.56", without thousands separator.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    pub thousands_separator: Option<char>,
+}
+
+#[cfg(feature = "std")]
+impl NumberFormat {
+    /// "1.234,56"
+    pub fn european() -> Self {
+        Self {
+            decimal_separator: ',',
+            thousands_separator: Some('.'),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            thousands_separator: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Money {
+    /// Prints the amount with two decimals and the separators of `format`.
+    pub fn format_with(&self, format: &NumberFormat) -> String {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.unsigned_abs();
+        let mut units = (cents / 100).to_string();
+        if let Some(separator) = format.thousands_separator {
+            let digits = units;
+            units = String::new();
+            for (index, digit) in digits.chars().enumerate() {
+                if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                    units.push(separator);
+                }
+                units.push(digit);
+            }
+        }
+        format!(
+            "{}{}{}{:02}",
+            sign,
+            units,
+            format.decimal_separator,
+            cents % 100
+        )
+    }
+}
+
+impl FromStr for Money {
+    type Err = &'static str;
+    /// Parses amounts like "27.99", "-0.5" or "12", with at most two decimals.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (units, decimals) = digits.split_once('.').unwrap_or((digits, "00"));
+        let valid = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !valid(units) || !valid(decimals) || decimals.len() > 2 {
+            return Err("amount is not valid");
+        }
+        let units: i64 = units.parse().map_err(|_| "amount is too large")?;
+        let decimals: i64 =
+            decimals.parse::<i64>().unwrap() * if decimals.len() == 1 { 10 } else { 1 };
+        let cents = units
+            .checked_mul(100)
+            .and_then(|cents| cents.checked_add(decimals))
+            .ok_or("amount is too large")?;
+        Ok(Money(if negative { -cents } else { cents }))
+    }
+}
+
+/// Money is serialized as a plain number of units, e.g. `27.99`.
+#[cfg(feature = "serde")]
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.0 as f64 / 100.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let amount = f64::deserialize(deserializer)? * 100.0;
+        if !amount.is_finite() || (amount - amount.round()).abs() > 1e-6 {
+            return Err(serde::de::Error::custom(
+                "amount must have at most two decimals",
+            ));
+        }
+        if amount.abs() >= i64::MAX as f64 {
+            return Err(serde::de::Error::custom("amount is too large"));
+        }
+        Ok(Money(amount.round() as i64))
+    }
+}
+
+/// Whether an item is imported, and therefore subject to import duty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Imported {
     Yes,
     No,
 }
 
-#[derive(Debug)]
-enum Category {
+/// Imported is serialized as a boolean.
+#[cfg(feature = "serde")]
+impl Serialize for Imported {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(matches!(self, Imported::Yes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Imported {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(if bool::deserialize(deserializer)? {
+            Imported::Yes
+        } else {
+            Imported::No
+        })
+    }
+}
+
+/// The category of an item, carrying its name.
+/// Books, food and medical products are exempt from the basic sales tax,
+/// excise goods (alcohol, tobacco) pay an excise duty on top of it, and
+/// services (e.g. gift wrapping) are never taxed, even when imported.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(tag = "kind", content = "name")
+)]
+pub enum Category {
     Book(String),
     Food(String),
     Medical(String),
+    Excise(String),
+    Service(String),
     Other(String),
 }
 
-pub trait Tax {
-    fn get_prices(&self) -> (f64, f64);
+/// The kind of a [`Category`], without the item name. Kinds are ordered as
+/// declared, books first and other goods last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CategoryKind {
+    Book,
+    Food,
+    Medical,
+    Excise,
+    Service,
+    Other,
 }
 
-#[derive(Debug)]
-pub struct Item {
-    clean_price: f64,
-    imported: Imported,
-    category: Category,
+#[cfg(feature = "std")]
+impl Category {
+    pub fn new(kind: CategoryKind, name: impl Into<String>) -> Self {
+        let name = name.into();
+        match kind {
+            CategoryKind::Book => Category::Book(name),
+            CategoryKind::Food => Category::Food(name),
+            CategoryKind::Medical => Category::Medical(name),
+            CategoryKind::Excise => Category::Excise(name),
+            CategoryKind::Service => Category::Service(name),
+            CategoryKind::Other => Category::Other(name),
+        }
+    }
+
+    pub fn kind(&self) -> CategoryKind {
+        match self {
+            Category::Book(_) => CategoryKind::Book,
+            Category::Food(_) => CategoryKind::Food,
+            Category::Medical(_) => CategoryKind::Medical,
+            Category::Excise(_) => CategoryKind::Excise,
+            Category::Service(_) => CategoryKind::Service,
+            Category::Other(_) => CategoryKind::Other,
+        }
+    }
+
+    /// The item name carried by the category, whatever its kind.
+    pub fn name(&self) -> &str {
+        match self {
+            Category::Book(name)
+            | Category::Food(name)
+            | Category::Medical(name)
+            | Category::Excise(name)
+            | Category::Service(name)
+            | Category::Other(name) => name,
+        }
+    }
 }
 
-impl Item {
-    fn new(clean_price: f64, imported: Imported, category: Category) -> Result<Self, &'static str> {
-        if clean_price < 0.0 {
-            return Err("clean_price must be positive");
+/// Keywords used to detect the category of an item from its description.
+/// Keywords are checked in registration order and the first one contained in
+/// the description wins, so a description matching several keywords always
+/// gets the same category. A description containing none of the keywords
+/// falls back to [`CategoryKind::Other`]. The case is ignored.
+///
+/// Keywords match whole words, or their plural with an "s" or "es": "wine"
+/// is found in "bottle of wines" but not in "ball of twine". See
+/// [`CategoryRules::match_inside_words`] for languages with compound words.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryRules {
+    rules: Vec<(String, CategoryKind)>,
+    inside_words: bool,
+}
+
+#[cfg(feature = "std")]
+impl CategoryRules {
+    /// Rules without any keyword: every item is categorized as `Other`.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            inside_words: false,
         }
-        Ok(Self {
-            clean_price,
-            imported,
-            category,
-        })
+    }
+
+    /// Lets keywords match anywhere in a word, so that "Schokolade" is found
+    /// in "Zartbitterschokolade".
+    pub fn match_inside_words(&mut self) -> &mut Self {
+        self.inside_words = true;
+        self
+    }
+
+    /// Adds a keyword with a lower priority than the ones already registered.
+    pub fn register(&mut self, keyword: impl Into<String>, kind: CategoryKind) -> &mut Self {
+        self.rules.push((keyword.into(), kind));
+        self
+    }
+
+    pub fn categorize(&self, description: &str) -> CategoryKind {
+        self.find(description).unwrap_or(CategoryKind::Other)
+    }
+
+    /// The kind of the first keyword found in `description`, `None` if there
+    /// is none.
+    pub fn find(&self, description: &str) -> Option<CategoryKind> {
+        let description = description.to_lowercase();
+        self.rules
+            .iter()
+            .find(|(keyword, _)| {
+                let keyword = keyword.to_lowercase();
+                if self.inside_words {
+                    description.contains(&keyword)
+                } else {
+                    contains_word(&description, &keyword)
+                }
+            })
+            .map(|(_, kind)| *kind)
+    }
+
+    /// The first keyword a word of `description` is a likely typo of: two
+    /// edits away from a keyword of six letters or more. Shorter keywords are
+    /// one edit away from many unrelated words ("cook", "wire", "bee"), so
+    /// they only match a truncation of at least four letters, e.g. "pill".
+    fn near_miss(&self, description: &str) -> Option<(&str, CategoryKind)> {
+        let description = description.to_lowercase();
+        description
+            .split_whitespace()
+            .filter(|word| word.chars().count() >= 3)
+            .find_map(|word| {
+                self.rules.iter().find(|(keyword, _)| {
+                    let keyword = keyword.to_lowercase();
+                    if keyword.chars().count() < 6 {
+                        word.chars().count() >= 4 && keyword.starts_with(word)
+                    } else {
+                        edit_distance(word, &keyword) <= 2
+                    }
+                })
+            })
+            .map(|(keyword, kind)| (keyword.as_str(), *kind))
+    }
+
+    /// The default keywords, in German.
+    pub fn german() -> Self {
+        let mut rules = Self::new();
+        rules
+            .match_inside_words()
+            .register("tabletten", CategoryKind::Medical)
+            .register("schokolade", CategoryKind::Food)
+            .register("buch", CategoryKind::Book)
+            .register("wein", CategoryKind::Excise)
+            .register("bier", CategoryKind::Excise)
+            .register("zigaretten", CategoryKind::Excise)
+            .register("tabak", CategoryKind::Excise);
+        rules
     }
 }
 
-impl ToString for Item {
-    fn to_string(&self) -> String {
-        println!("{:?}", self);
-        let name = match &self.category {
-            Category::Book(x) | Category::Food(x) | Category::Medical(x) | Category::Other(x) => x,
-        };
-        let prefix = if matches!(self.imported, Imported::Yes) {
-            "1 imported "
+#[cfg(feature = "std")]
+impl Default for CategoryRules {
+    /// The keywords of the kata items, by priority: "service" and "gift
+    /// wrap" first, then "pills" (medical), "chocolate" (food) and "book",
+    /// followed by a few excise goods.
+    fn default() -> Self {
+        let mut rules = Self::new();
+        rules
+            .register("service", CategoryKind::Service)
+            .register("gift wrap", CategoryKind::Service)
+            .register("pills", CategoryKind::Medical)
+            .register("chocolate", CategoryKind::Food)
+            .register("book", CategoryKind::Book)
+            .register("wine", CategoryKind::Excise)
+            .register("beer", CategoryKind::Excise)
+            .register("cigarettes", CategoryKind::Excise)
+            .register("tobacco", CategoryKind::Excise);
+        rules
+    }
+}
+
+/// The rules used to tax an item: the basic rate, or the one `category_rates`
+/// gives to the category, applies to every category not listed in `exempt`,
+/// the excise rate to excise goods, the import rate to every imported item
+/// whose category isn't listed in `import_exempt`, and the resulting tax is
+/// rounded to a multiple of `rounding_increment` following `rounding`, per
+/// item or once for the basket depending on `round_per`. Items with a unit
+/// price below `exempt_below` pay no tax at all.
+///
+/// With the `serde` feature a policy can be read from JSON (and from TOML with
+/// the `toml` feature), the missing fields keeping their default value.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct TaxPolicy {
+    pub basic_rate: f64,
+    pub category_rates: HashMap<CategoryKind, f64>,
+    pub import_rate: f64,
+    pub excise_rate: f64,
+    pub rounding_increment: Money,
+    pub rounding: RoundingStrategy,
+    pub round_per: RoundScope,
+    pub exempt: HashSet<CategoryKind>,
+    pub import_exempt: HashSet<CategoryKind>,
+    pub exempt_below: Option<Money>,
+}
+
+#[cfg(feature = "std")]
+impl Default for TaxPolicy {
+    /// The rules of the kata: 10% basic rate, 5% import duty, rounded up to
+    /// 0.05, with books, food and medical products exempt from the basic rate.
+    /// Excise goods, which the kata doesn't have, pay an extra 10%.
+    fn default() -> Self {
+        Self {
+            basic_rate: 0.10,
+            category_rates: HashMap::new(),
+            import_rate: 0.05,
+            excise_rate: 0.10,
+            rounding_increment: ROUND_INCREMENT,
+            rounding: RoundingStrategy::Ceil,
+            round_per: RoundScope::Item,
+            exempt: HashSet::from([
+                CategoryKind::Book,
+                CategoryKind::Food,
+                CategoryKind::Medical,
+            ]),
+            import_exempt: HashSet::new(),
+            exempt_below: None,
+        }
+    }
+}
+
+/// How a tax falling between two multiples of the rounding increment is rounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RoundingStrategy {
+    /// Always up to the next multiple, as the kata requires.
+    #[default]
+    Ceil,
+    /// To the nearest multiple, halves going up.
+    HalfUp,
+    /// To the nearest multiple, halves going to the even multiple.
+    Bankers,
+}
+
+/// The reasons a policy can fail to be loaded.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    /// The document is malformed, with the message of the parser.
+    Parse(String),
+    /// A rate is outside of [0, 1].
+    InvalidRate,
+    /// The rounding increment isn't positive.
+    InvalidIncrement,
+    /// The exemption threshold is negative.
+    InvalidThreshold,
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolicyError::Parse(message) => write!(f, "invalid policy: {}", message),
+            PolicyError::InvalidRate => f.write_str("rates must be between 0 and 1"),
+            PolicyError::InvalidIncrement => f.write_str("rounding_increment must be positive"),
+            PolicyError::InvalidThreshold => f.write_str("exempt_below can't be negative"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for PolicyError {}
+
+#[cfg(feature = "serde")]
+impl TaxPolicy {
+    /// Reads a policy from JSON, e.g.
+    /// `{"basic_rate": 0.2, "exempt": ["Book", "Medical"]}`.
+    pub fn from_json_str(s: &str) -> Result<Self, PolicyError> {
+        let policy: TaxPolicy =
+            serde_json::from_str(s).map_err(|e| PolicyError::Parse(e.to_string()))?;
+        policy.validated()
+    }
+
+    /// Reads a policy from TOML, with the same fields as
+    /// [`TaxPolicy::from_json_str`].
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Self, PolicyError> {
+        let policy: TaxPolicy = toml::from_str(s).map_err(|e| PolicyError::Parse(e.to_string()))?;
+        policy.validated()
+    }
+
+    fn validated(self) -> Result<Self, PolicyError> {
+        let mut rates = [self.basic_rate, self.import_rate, self.excise_rate]
+            .into_iter()
+            .chain(self.category_rates.values().copied());
+        if !rates.all(|rate| (0.0..=1.0).contains(&rate)) {
+            return Err(PolicyError::InvalidRate);
+        }
+        if self.rounding_increment <= Money::ZERO {
+            return Err(PolicyError::InvalidIncrement);
+        }
+        if self
+            .exempt_below
+            .is_some_and(|threshold| threshold < Money::ZERO)
+        {
+            return Err(PolicyError::InvalidThreshold);
+        }
+        Ok(self)
+    }
+}
+
+/// What the tax is rounded on. Receipt lines always show the tax rounded per
+/// item, only the basket totals of a [`Calculator`] depend on the scope.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RoundScope {
+    /// Every item is rounded, as the kata requires.
+    #[default]
+    Item,
+    /// The tax of the whole basket is rounded once.
+    Basket,
+}
+
+#[cfg(feature = "std")]
+impl TaxPolicy {
+    fn is_below_threshold(&self, price: Money) -> bool {
+        self.exempt_below.is_some_and(|threshold| price < threshold)
+    }
+
+    fn basic_rate_for(&self, kind: CategoryKind) -> f64 {
+        if kind == CategoryKind::Service || self.exempt.contains(&kind) {
+            0.0
         } else {
-            "1 "
-        };
-        format!(
-            "{}{}: {:.2}",
-            prefix,
-            name,
-            ((self.get_prices().0 + self.get_prices().1) * 100.0).round() / 100.0
-        )
+            self.category_rates
+                .get(&kind)
+                .copied()
+                .unwrap_or(self.basic_rate)
+        }
+    }
+
+    fn excise_rate_for(&self, kind: CategoryKind) -> f64 {
+        match kind {
+            CategoryKind::Excise => self.excise_rate,
+            _ => 0.0,
+        }
+    }
+
+    fn import_rate_for(&self, kind: CategoryKind, imported: Imported) -> f64 {
+        match imported {
+            Imported::Yes if !self.import_exempt.contains(&kind) => self.import_rate,
+            _ => 0.0,
+        }
     }
 }
 
-fn round_numbers(number: f64) -> f64 {
-    (number * 20.0).round() / 20.0
+/// A currency prices can be expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Currency {
+    Usd,
+    Eur,
+    Gbp,
 }
 
-impl Tax for Item {
-    fn get_prices(&self) -> (f64, f64) {
-        match (&self.category, &self.imported) {
-            (Category::Book(_) | Category::Food(_) | Category::Medical(_), Imported::No) => {
-                (self.clean_price, 0.0)
+impl Currency {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::Usd => "$",
+            Currency::Eur => "€",
+            Currency::Gbp => "£",
+        }
+    }
+
+    /// The ISO 4217 code, e.g. "USD".
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+        }
+    }
+
+    #[cfg(feature = "std")]
+    const ALL: [Currency; 3] = [Currency::Usd, Currency::Eur, Currency::Gbp];
+}
+
+/// Parses a price like "12.49", "$12.49" or "12.49 USD", returning the
+/// currency it is expressed in, if any. With `round`, more than two decimals
+/// are rounded half up to the cent instead of being rejected.
+#[cfg(feature = "std")]
+fn parse_price(s: &str, round: bool) -> Result<(Money, Option<Currency>), ParseItemError> {
+    let s = s.trim();
+    let (amount, currency) = Currency::ALL
+        .iter()
+        .find_map(|currency| {
+            s.strip_prefix(currency.symbol())
+                .or_else(|| s.strip_suffix(currency.code()).map(str::trim_end))
+                .map(|amount| (amount, Some(*currency)))
+        })
+        .unwrap_or((s, None));
+    let amount = match amount.split_once('.') {
+        Some((units, decimals)) if round && decimals.len() > 2 => {
+            if !decimals.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseItemError::InvalidPrice);
             }
-            (Category::Other(_), Imported::No) => {
-                (self.clean_price, round_numbers(self.clean_price * 0.10))
+            let (cents, rest) = decimals.split_at(2);
+            let amount: Money = format!("{}.{}", units, cents)
+                .parse()
+                .map_err(|_| ParseItemError::InvalidPrice)?;
+            match (rest.as_bytes()[0] >= b'5', units.starts_with('-')) {
+                (false, _) => amount,
+                (true, false) => amount + Money::from_cents(1),
+                (true, true) => amount - Money::from_cents(1),
             }
-            (Category::Book(_) | Category::Food(_) | Category::Medical(_), Imported::Yes) => {
-                (self.clean_price, round_numbers(self.clean_price * (0.05)))
+        }
+        _ => amount.parse().map_err(|_| ParseItemError::InvalidPrice)?,
+    };
+    Ok((amount, currency))
+}
+
+/// A clean price and its tax, which can be summed over the pairs returned
+/// by [`Tax::get_prices`]: `basket.iter().map(Tax::get_prices).sum::<Prices>()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Prices {
+    pub clean: Money,
+    pub tax: Money,
+}
+
+impl Prices {
+    /// The price taxes included.
+    pub fn total(&self) -> Money {
+        self.clean + self.tax
+    }
+}
+
+impl From<(Money, Money)> for Prices {
+    fn from((clean, tax): (Money, Money)) -> Self {
+        Self { clean, tax }
+    }
+}
+
+impl Add for Prices {
+    type Output = Prices;
+    fn add(self, other: Prices) -> Prices {
+        Prices {
+            clean: self.clean + other.clean,
+            tax: self.tax + other.tax,
+        }
+    }
+}
+
+impl Sum for Prices {
+    fn sum<I: Iterator<Item = Prices>>(iter: I) -> Prices {
+        iter.fold(Prices::default(), Add::add)
+    }
+}
+
+impl Sum<(Money, Money)> for Prices {
+    fn sum<I: Iterator<Item = (Money, Money)>>(iter: I) -> Prices {
+        iter.map(Prices::from).sum()
+    }
+}
+
+pub trait Tax {
+    fn get_prices(&self) -> (Money, Money);
+    /// The currency of the prices, `None` for plain amounts.
+    fn currency(&self) -> Option<Currency> {
+        None
+    }
+}
+
+/// The reasons an item can fail to be built or parsed.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseItemError {
+    MissingAt,
+    MissingQuantity,
+    InvalidQuantity,
+    InvalidPrice,
+    NegativePrice,
+    MissingPrice,
+    MissingName,
+    InvalidDiscount,
+    Overflow,
+    InvalidImported,
+    UnknownCategory,
+    MixedCurrencies,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ParseItemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            ParseItemError::MissingAt => "Invalid string: missing 'at'",
+            ParseItemError::MissingQuantity => "Invalid string: missing quantity",
+            ParseItemError::InvalidQuantity => {
+                "Quantity must be a non-zero integer or a positive weight"
             }
-            (Category::Other(_), Imported::Yes) => (
-                self.clean_price,
-                round_numbers(self.clean_price * (0.10 + 0.05)),
-            ),
+            ParseItemError::InvalidPrice => "Price is not valid",
+            ParseItemError::NegativePrice => "clean_price must be positive",
+            ParseItemError::MissingPrice => "Missing price",
+            ParseItemError::MissingName => "Missing name",
+            ParseItemError::InvalidDiscount => "Discount must be between 0 and 1",
+            ParseItemError::Overflow => "Quantity times price is too large",
+            ParseItemError::InvalidImported => "Imported must be yes or no",
+            ParseItemError::UnknownCategory => "No category matches the description",
+            ParseItemError::MixedCurrencies => "Currency differs from the previous items",
+        };
+        f.write_str(message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ParseItemError {}
+
+/// How much of an item is bought: a number of units, or a weight for goods
+/// sold by weight (e.g. "0.5 kg of rice"), whose price is then per unit of
+/// weight. Weights are counted to the thousandth.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
+pub enum Quantity {
+    /// Negative for returned items.
+    Count(i32),
+    Weight(f64),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Quantity::Count(count) => count.fmt(f),
+            Quantity::Weight(weight) => weight.fmt(f),
         }
     }
 }
 
-impl FromStr for Item {
-    type Err = String;
+#[cfg(feature = "std")]
+impl FromStr for Quantity {
+    type Err = ParseItemError;
+    /// Parses "3" as a count and "0.5" as a weight. Anything that doesn't look
+    /// like a number is a missing quantity, a number that isn't a valid
+    /// quantity (too large) is an invalid one. Negative counts are returns.
+    /// The English words "a", "an" and "one" to "ten" are counts too.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        println!("{s}");
-        let components: Vec<&str> = s.split(" at ").collect();
-        if components.len() != 2 {
-            return Err("Invalid string: missing 'at'".to_string());
-        }
-        let descr = components[0];
-        let price = components[1].parse().map_err(|_| "Price is not valid")?;
-        let imported = if descr.contains("imported") {
-            Imported::Yes
-        } else {
-            Imported::No
-        };
-        let category = if descr.contains("pills") {
-            Category::Medical("packet of headache pills".to_string())
-        } else if descr.contains("chocolates") & descr.contains("box") {
-            Category::Food("box of chocolates".to_string())
-        } else if descr.contains("chocolate") & descr.contains("bar") {
-            Category::Food("chocolate bar".to_string())
-        } else if descr.contains("book") {
-            Category::Book("book".to_string())
-        } else {
-            let category: &str = match imported {
-                Imported::Yes => descr.split_once("imported ").unwrap().1,
-                Imported::No => descr.split_once(" ").unwrap().1,
-            };
+        const WORDS: [&str; 10] = [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+        ];
+        let word = s.to_ascii_lowercase();
+        if word == "a" || word == "an" {
+            return Ok(Quantity::Count(1));
+        }
+        if let Some(index) = WORDS.iter().position(|w| *w == word) {
+            return Ok(Quantity::Count(index as i32 + 1));
+        }
+        let digits = s.strip_prefix('-').unwrap_or(s);
+        let valid = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        match digits.split_once('.') {
+            Some((units, decimals)) if valid(units) && valid(decimals) => s
+                .parse()
+                .map(Quantity::Weight)
+                .map_err(|_| ParseItemError::InvalidQuantity),
+            None if valid(digits) => s
+                .parse()
+                .map(Quantity::Count)
+                .map_err(|_| ParseItemError::InvalidQuantity),
+            _ => Err(ParseItemError::MissingQuantity),
+        }
+    }
+}
 
-            Category::Other(category.to_string())
-        };
-        Item::new(price, imported, category).map_err(|e| e.to_string())
+/// Deserializing an item goes through [`Item::with_quantity`], so it fails
+/// where building it would.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "RawItem")
+)]
+pub struct Item {
+    quantity: Quantity,
+    clean_price: Money,
+    imported: Imported,
+    category: Category,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    currency: Option<Currency>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    plural: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    discount: Option<f64>,
+}
+
+/// The fields of a serialized [`Item`], before they are checked.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawItem {
+    quantity: Quantity,
+    clean_price: Money,
+    imported: Imported,
+    category: Category,
+    #[serde(default)]
+    currency: Option<Currency>,
+    #[serde(default)]
+    plural: Option<String>,
+    #[serde(default)]
+    discount: Option<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawItem> for Item {
+    type Error = ParseItemError;
+    fn try_from(raw: RawItem) -> Result<Self, Self::Error> {
+        let mut item =
+            Item::with_quantity(raw.quantity, raw.clean_price, raw.imported, raw.category)?;
+        if let Some(discount) = raw.discount {
+            item = item.with_discount(discount)?;
+        }
+        item.currency = raw.currency;
+        item.plural = raw.plural;
+        Ok(item)
     }
 }
 
-pub struct Basket<T: Tax + ToString> {
-    elements: Vec<T>,
+/// Equality and hashing cover every field. The discount is validated to be
+/// between 0 and 1 and the weight to be finite, so neither is NaN and
+/// equality is total, unlike for a bare [`Quantity`].
+#[cfg(feature = "std")]
+impl Eq for Item {}
+
+#[cfg(feature = "std")]
+impl Hash for Item {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Weights are validated to be finite, so they are hashed by their
+        // bits, 0.0 and -0.0 being equal.
+        match self.quantity {
+            Quantity::Count(count) => (0u8, count).hash(state),
+            Quantity::Weight(weight) => (1u8, (weight + 0.0).to_bits()).hash(state),
+        }
+        self.clean_price.hash(state);
+        self.imported.hash(state);
+        self.category.hash(state);
+        self.currency.hash(state);
+        self.plural.hash(state);
+        self.discount
+            .map(|discount| (discount + 0.0).to_bits())
+            .hash(state);
+    }
 }
 
-impl<T> Basket<T>
-where
-    T: Tax + ToString,
-{
-    fn new(elements: Vec<T>) -> Self {
-        Self { elements }
+#[cfg(feature = "std")]
+impl Item {
+    /// The largest accepted clean price, of one unit and of the whole line
+    /// (`quantity * clean_price` in absolute value). It leaves room for the
+    /// taxes and the basis point arithmetic of the rounding to stay in an
+    /// `i64`.
+    pub const MAX_LINE_TOTAL: Money = Money(1_000_000_000_000);
+
+    /// Builds an item, failing if the quantity is zero, the price is negative
+    /// or the line total is above [`Item::MAX_LINE_TOTAL`]. A negative
+    /// quantity is a return: its price and its tax are refunded.
+    pub fn new(
+        quantity: i32,
+        clean_price: Money,
+        imported: Imported,
+        category: Category,
+    ) -> Result<Self, ParseItemError> {
+        Self::with_quantity(Quantity::Count(quantity), clean_price, imported, category)
     }
-    fn get_total(&self) -> f64 {
-        self.elements
-            .iter()
-            .fold(0.0, |acc, x| acc + x.get_prices().0 + x.get_prices().1)
+
+    /// Same as [`Item::new`] for arguments known to be valid, such as literals
+    /// in tests.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the validation error if [`Item::new`] would fail.
+    pub fn from_valid(
+        quantity: i32,
+        clean_price: Money,
+        imported: Imported,
+        category: Category,
+    ) -> Self {
+        Self::new(quantity, clean_price, imported, category)
+            .unwrap_or_else(|error| panic!("invalid item: {}", error))
+    }
+
+    /// Builds one unit of an item from its price taxes included, with the
+    /// default policy. The clean price is the highest one whose price with
+    /// taxes doesn't exceed `gross_price`.
+    ///
+    /// Since taxes are rounded up, some gross prices can't be reached: with a
+    /// 10% rate 0.50 costs 0.55 and 0.51 already costs 0.61, so nothing costs
+    /// 0.56 to 0.60. Such a gross price gives the item just below it, here
+    /// 0.50, which costs slightly less than `gross_price`.
+    pub fn from_gross(
+        gross_price: Money,
+        imported: Imported,
+        category: Category,
+    ) -> Result<Self, ParseItemError> {
+        if gross_price < Money::ZERO {
+            return Err(ParseItemError::NegativePrice);
+        }
+        if gross_price > Self::MAX_LINE_TOTAL {
+            return Err(ParseItemError::Overflow);
+        }
+        let policy = TaxPolicy::default();
+        let gross = |clean_price: Money| {
+            clean_price + compute_tax(clean_price, imported, &category, &policy)
+        };
+        let kind = category.kind();
+        let rate = policy.basic_rate_for(kind)
+            + policy.excise_rate_for(kind)
+            + policy.import_rate_for(kind, imported);
+        let basis_points = (rate * 10_000.0).round() as i64;
+        // Taxes are rounded up, so no clean price above the exact one fits.
+        let mut clean_price =
+            Money::from_cents(gross_price.cents() * 10_000 / (10_000 + basis_points));
+        while clean_price > Money::ZERO && gross(clean_price) > gross_price {
+            clean_price = clean_price - Money::from_cents(1);
+        }
+        Self::new(1, clean_price, imported, category)
+    }
+
+    /// Builds an item sold by weight, `price_per_unit` being the price of one
+    /// unit of weight. The line total is rounded half up to the cent and the
+    /// taxes are computed on it. Fails like [`Item::new`], a weight below one
+    /// thousandth being invalid.
+    pub fn weighed(
+        weight: f64,
+        price_per_unit: Money,
+        imported: Imported,
+        category: Category,
+    ) -> Result<Self, ParseItemError> {
+        Self::with_quantity(Quantity::Weight(weight), price_per_unit, imported, category)
+    }
+
+    /// Builds an item with either kind of quantity, see [`Item::new`] and
+    /// [`Item::weighed`].
+    pub fn with_quantity(
+        quantity: Quantity,
+        clean_price: Money,
+        imported: Imported,
+        category: Category,
+    ) -> Result<Self, ParseItemError> {
+        if clean_price < Money::ZERO {
+            return Err(ParseItemError::NegativePrice);
+        }
+        if clean_price > Self::MAX_LINE_TOTAL {
+            return Err(ParseItemError::Overflow);
+        }
+        let total = match quantity {
+            Quantity::Count(0) => return Err(ParseItemError::InvalidQuantity),
+            Quantity::Count(count) => clean_price.checked_mul(count),
+            Quantity::Weight(weight) => {
+                let thousandths = thousandths(weight).ok_or(ParseItemError::InvalidQuantity)?;
+                weigh(clean_price, thousandths)
+            }
+        };
+        match total {
+            Some(total) if total.cents().unsigned_abs() <= Self::MAX_LINE_TOTAL.0 as u64 => {}
+            _ => return Err(ParseItemError::Overflow),
+        }
+        Ok(Self {
+            quantity,
+            clean_price,
+            imported,
+            category,
+            currency: None,
+            plural: None,
+            discount: None,
+        })
+    }
+
+    pub fn with_currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    /// Sets the name printed when the quantity is above one, for nouns the
+    /// naive pluralization gets wrong.
+    pub fn with_plural(mut self, plural: impl Into<String>) -> Self {
+        self.plural = Some(plural.into());
+        self
+    }
+
+    /// Applies a discount, as a fraction of the price between 0 and 1, before
+    /// taxes are computed.
+    pub fn with_discount(mut self, discount: f64) -> Result<Self, ParseItemError> {
+        if !(0.0..=1.0).contains(&discount) {
+            return Err(ParseItemError::InvalidDiscount);
+        }
+        self.discount = Some(discount);
+        Ok(self)
+    }
+
+    /// Replaces the clean price of one unit, with the checks of
+    /// [`Item::with_quantity`]; the item is left unchanged on error.
+    pub fn set_price(&mut self, clean_price: Money) -> Result<(), ParseItemError> {
+        Item::with_quantity(
+            self.quantity,
+            clean_price,
+            self.imported,
+            self.category.clone(),
+        )?;
+        self.clean_price = clean_price;
+        Ok(())
+    }
+
+    /// The price of one unit once the discount is applied, rounded to the cent.
+    fn unit_price(&self) -> Money {
+        match self.discount {
+            Some(discount) => discounted(self.clean_price, discount),
+            None => self.clean_price,
+        }
+    }
+
+    /// The description of the item, without quantity nor "imported".
+    pub fn name(&self) -> &str {
+        self.category.name()
+    }
+
+    /// The amount the tax is computed on, and how many times it is due: the
+    /// unit price and the count, or the weighed line price once.
+    fn taxed_amount(&self) -> (Money, i32) {
+        match self.quantity {
+            Quantity::Count(count) => (self.unit_price(), count),
+            Quantity::Weight(weight) => {
+                // `with_quantity` rejects the weights this can't price.
+                let price = thousandths(weight)
+                    .and_then(|thousandths| weigh(self.unit_price(), thousandths))
+                    .unwrap_or(Money::ZERO);
+                (price, 1)
+            }
+        }
+    }
+
+    /// Whether both items only differ by their quantity.
+    fn same_product(&self, other: &Item) -> bool {
+        self.name() == other.name()
+            && self.imported == other.imported
+            && self.clean_price == other.clean_price
+            && self.discount == other.discount
+    }
+
+    /// Same as [`Tax::get_prices`], with the taxes computed from `policy`.
+    pub fn get_prices_with(&self, policy: &TaxPolicy) -> (Money, Money) {
+        let (amount, times) = self.taxed_amount();
+        debug_assert!(amount
+            .checked_mul(times)
+            .is_some_and(|total| total.cents().unsigned_abs() <= Self::MAX_LINE_TOTAL.0 as u64));
+        let tax = compute_tax(amount, self.imported, &self.category, policy);
+        (amount * times, tax * times)
+    }
+
+    /// The tax of the whole line before rounding, in ten-thousandths of a cent.
+    fn raw_tax_with(&self, policy: &TaxPolicy) -> i128 {
+        let (amount, times) = self.taxed_amount();
+        raw_tax(amount, self.imported, &self.category, policy) * i128::from(times)
+    }
+
+    /// The tax of the line before and after rounding, to reconcile a total
+    /// with a manual computation: 4.1985 and 4.20 for an imported perfume at
+    /// 27.99. The unrounded tax is in units of money, not cents.
+    pub fn tax_with_audit(&self) -> (f64, Money) {
+        let policy = TaxPolicy::default();
+        let raw = self.raw_tax_with(&policy) as f64 / 1_000_000.0;
+        (raw, self.get_prices_with(&policy).1)
+    }
+
+    /// Splits the tax of [`Tax::get_prices`] into basic tax, excise and import duty.
+    pub fn tax_breakdown(&self) -> TaxBreakdown {
+        self.tax_breakdown_with(&TaxPolicy::default())
+    }
+
+    /// Splits the tax of [`Item::get_prices_with`] into basic tax, excise and import duty.
+    pub fn tax_breakdown_with(&self, policy: &TaxPolicy) -> TaxBreakdown {
+        let (_, total_tax) = self.get_prices_with(policy);
+        let (amount, times) = self.taxed_amount();
+        if policy.is_below_threshold(amount) {
+            return TaxBreakdown {
+                base_tax: Money::ZERO,
+                excise_tax: Money::ZERO,
+                import_tax: Money::ZERO,
+                total_tax,
+            };
+        }
+        let kind = self.category.kind();
+        let base_rate = policy.basic_rate_for(kind);
+        let round =
+            |rate| round_numbers(amount, rate, policy.rounding_increment, policy.rounding) * times;
+        let base_tax = round(base_rate);
+        let domestic_tax = round(base_rate + policy.excise_rate_for(kind));
+        TaxBreakdown {
+            base_tax,
+            excise_tax: domestic_tax - base_tax,
+            import_tax: total_tax - domestic_tax,
+            total_tax,
+        }
+    }
+}
+
+/// The tax of an item split by its origin. Taxes are rounded on the combined
+/// rate, so `base_tax` is rounded on its own, `excise_tax` is what the excise
+/// adds to it and `import_tax` is what is left of `total_tax`: the parts
+/// always add up to the total.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaxBreakdown {
+    pub base_tax: Money,
+    pub excise_tax: Money,
+    pub import_tax: Money,
+    pub total_tax: Money,
+}
+
+/// Builds an [`Item`] field by field. The price and the name are required;
+/// the quantity defaults to 1, the item to not imported and the category to
+/// [`CategoryKind::Other`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct ItemBuilder {
+    quantity: Option<Quantity>,
+    price: Option<Money>,
+    imported: Option<Imported>,
+    category: Option<CategoryKind>,
+    name: Option<String>,
+    discount: Option<f64>,
+}
+
+#[cfg(feature = "std")]
+impl ItemBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn quantity(mut self, quantity: i32) -> Self {
+        self.quantity = Some(Quantity::Count(quantity));
+        self
+    }
+
+    /// Sells the item by weight, the price being per unit of weight.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.quantity = Some(Quantity::Weight(weight));
+        self
+    }
+
+    pub fn price(mut self, price: Money) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    pub fn imported(mut self, imported: Imported) -> Self {
+        self.imported = Some(imported);
+        self
+    }
+
+    pub fn category(mut self, category: CategoryKind) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn discount(mut self, discount: f64) -> Self {
+        self.discount = Some(discount);
+        self
+    }
+
+    /// Builds the item with the same validation as [`Item::new`] and
+    /// [`Item::with_discount`].
+    pub fn build(self) -> Result<Item, ParseItemError> {
+        let price = self.price.ok_or(ParseItemError::MissingPrice)?;
+        let name = self.name.ok_or(ParseItemError::MissingName)?;
+        let category = Category::new(self.category.unwrap_or(CategoryKind::Other), name);
+        let item = Item::with_quantity(
+            self.quantity.unwrap_or(Quantity::Count(1)),
+            price,
+            self.imported.unwrap_or(Imported::No),
+            category,
+        )?;
+        match self.discount {
+            Some(discount) => item.with_discount(discount),
+            None => Ok(item),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Item {
+    pub fn builder() -> ItemBuilder {
+        ItemBuilder::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Item {
+    /// The line of the item on a receipt, with the name as printed and the
+    /// taxes computed from `policy`.
+    fn receipt_line(&self, policy: &TaxPolicy) -> ReceiptLine {
+        // Weights are printed as given: "0.5 kg of rice", "2.5 kg of rice".
+        let name = match (&self.plural, self.quantity) {
+            (_, Quantity::Count(1 | -1)) | (_, Quantity::Weight(_)) => self.name().to_string(),
+            (Some(plural), _) => plural.clone(),
+            (None, _) => pluralize(self.name()),
+        };
+        let (clean_price, tax) = self.get_prices_with(policy);
+        let (amount, _) = self.taxed_amount();
+        ReceiptLine {
+            name,
+            imported: self.imported,
+            quantity: self.quantity,
+            unit_clean: self.unit_price(),
+            unit_tax: compute_tax(amount, self.imported, &self.category, policy),
+            tax,
+            tax_rate: if clean_price == Money::ZERO {
+                0.0
+            } else {
+                tax.cents() as f64 / clean_price.cents() as f64
+            },
+            line_total: clean_price + tax,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = self.currency.map_or("", |currency| currency.symbol());
+        f.write_str(
+            &self
+                .receipt_line(&TaxPolicy::default())
+                .to_string_with(symbol, &NumberFormat::default()),
+        )
+    }
+}
+
+/// Whether `keyword` is in `description` as whole words, the last one
+/// possibly followed by "s" or "es".
+#[cfg(feature = "std")]
+fn contains_word(description: &str, keyword: &str) -> bool {
+    let ends_word = |rest: &str| !rest.starts_with(char::is_alphanumeric);
+    description.match_indices(keyword).any(|(start, _)| {
+        let after = &description[start + keyword.len()..];
+        !description[..start].ends_with(char::is_alphanumeric)
+            && (ends_word(after)
+                || ["s", "es"]
+                    .iter()
+                    .any(|end| after.strip_prefix(end).is_some_and(ends_word)))
+    })
+}
+
+/// The Levenshtein distance between `a` and `b`, in characters.
+#[cfg(feature = "std")]
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Naive English plural of a description: the noun before " of ", or else the
+/// last word, gets an "s", "es" or "ies" depending on its ending.
+#[cfg(feature = "std")]
+fn pluralize(name: &str) -> String {
+    let (head, tail) = name.split_at(name.find(" of ").unwrap_or(name.len()));
+    let (before, noun) = head.split_at(head.rfind(' ').map_or(0, |index| index + 1));
+    let ends_with = |ends: &[&str]| ends.iter().any(|end| noun.ends_with(end));
+    let noun = if ends_with(&["s", "x", "z", "ch", "sh"]) {
+        format!("{}es", noun)
+    } else if noun.ends_with('y') && !ends_with(&["ay", "ey", "iy", "oy", "uy"]) {
+        format!("{}ies", &noun[..noun.len() - 1])
+    } else {
+        format!("{}s", noun)
+    };
+    format!("{}{}{}", before, noun, tail)
+}
+
+/// `item` parsed from the description `name`: a description given for
+/// several units that already looks plural ("3 books") is printed as is,
+/// other ones ("3 book") are pluralized.
+#[cfg(feature = "std")]
+fn with_given_plural(item: Item, name: String) -> Item {
+    let several = matches!(item.quantity, Quantity::Count(count) if count.unsigned_abs() > 1);
+    if several && looks_plural(&name) {
+        item.with_plural(name)
+    } else {
+        item
+    }
+}
+
+/// Whether the noun [`pluralize`] would change already ends with an "s".
+#[cfg(feature = "std")]
+fn looks_plural(name: &str) -> bool {
+    let head = &name[..name.find(" of ").unwrap_or(name.len())];
+    head.rsplit(' ')
+        .next()
+        .is_some_and(|noun| noun.ends_with('s'))
+}
+
+/// The number of units of a quantity, a weight counting as one.
+#[cfg(feature = "std")]
+fn units(quantity: Quantity) -> i32 {
+    match quantity {
+        Quantity::Count(count) => count,
+        Quantity::Weight(_) => 1,
+    }
+}
+
+/// `price` reduced by `fraction`, the reduction being rounded half up to the cent.
+#[cfg(feature = "std")]
+fn discounted(price: Money, fraction: f64) -> Money {
+    let basis_points = (fraction * 10_000.0).round() as i128;
+    let off = (i128::from(price.cents()) * basis_points + 5_000).div_euclid(10_000);
+    // `off` is at most `price`, `fraction` being at most one.
+    price - Money::from_cents(off as i64)
+}
+
+/// `weight` in thousandths, `None` if it isn't a positive number of them.
+#[cfg(feature = "std")]
+fn thousandths(weight: f64) -> Option<i64> {
+    let thousandths = (weight * 1_000.0).round();
+    (thousandths >= 1.0 && thousandths < i64::MAX as f64).then_some(thousandths as i64)
+}
+
+/// The price of `thousandths` of a unit of weight, rounded half up to the cent.
+#[cfg(feature = "std")]
+fn weigh(price: Money, thousandths: i64) -> Option<Money> {
+    let raw = price.cents().checked_mul(thousandths)?.checked_add(500)?;
+    Some(Money::from_cents(raw.div_euclid(1_000)))
+}
+
+/// Computes `rate` of `amount` rounded to a multiple of `increment` with
+/// `strategy` (an increment below one cent rounds to the cent).
+/// The rate is turned into basis points so the rounding works on integers:
+/// `amount * basis_points` is expressed in ten-thousandths of a cent.
+pub fn round_numbers(
+    amount: Money,
+    rate: f64,
+    increment: Money,
+    strategy: RoundingStrategy,
+) -> Money {
+    let basis_points = round(rate * 10_000.0) as i128;
+    round_raw(
+        i128::from(amount.cents()) * basis_points,
+        increment,
+        strategy,
+    )
+}
+
+/// Rounds `amount` up, toward positive infinity, to the next multiple of
+/// `increment`: with an increment of 0.05, 0.56 becomes 0.60 and -0.56
+/// becomes -0.55. Amounts that already are a multiple are kept as they are,
+/// and an increment below one cent rounds to the cent.
+pub fn round_tax(amount: Money, increment: Money) -> Money {
+    round_raw(
+        i128::from(amount.cents()) * 10_000,
+        increment,
+        RoundingStrategy::Ceil,
+    )
+}
+
+/// The increment taxes are rounded to by the kata, 0.05, and by the default
+/// [`TaxPolicy`].
+pub const ROUND_INCREMENT: Money = Money(5);
+
+/// [`round_tax`] to [`ROUND_INCREMENT`].
+pub fn round_tax_default(amount: Money) -> Money {
+    round_tax(amount, ROUND_INCREMENT)
+}
+
+/// Rounds a float to the nearest integer, half away from zero.
+#[cfg(feature = "std")]
+fn round(x: f64) -> f64 {
+    x.round()
+}
+
+#[cfg(not(feature = "std"))]
+fn round(x: f64) -> f64 {
+    libm::round(x)
+}
+
+/// Rounds `raw`, in ten-thousandths of a cent, to a multiple of `increment`.
+/// The result saturates at the bounds of an `i64` of cents.
+fn round_raw(raw: i128, increment: Money, strategy: RoundingStrategy) -> Money {
+    let increment = i128::from(increment.cents().max(1));
+    let step = increment * 10_000;
+    let (steps, rest) = (raw.div_euclid(step), raw.rem_euclid(step));
+    let round_up = match strategy {
+        RoundingStrategy::Ceil => rest > 0,
+        RoundingStrategy::HalfUp => 2 * rest >= step,
+        RoundingStrategy::Bankers => {
+            2 * rest > step || (2 * rest == step && steps.rem_euclid(2) == 1)
+        }
+    };
+    let cents = (steps + i128::from(round_up)) * increment;
+    Money::from_cents(cents.clamp(i64::MIN.into(), i64::MAX.into()) as i64)
+}
+
+/// The rounded tax of a single unit sold at `price`, without building an [`Item`].
+#[cfg(feature = "std")]
+pub fn compute_tax(
+    price: Money,
+    imported: Imported,
+    category: &Category,
+    policy: &TaxPolicy,
+) -> Money {
+    let raw = raw_tax(price, imported, category, policy);
+    round_raw(raw, policy.rounding_increment, policy.rounding)
+}
+
+/// The tax of a single unit sold at `price` before rounding, in
+/// ten-thousandths of a cent.
+#[cfg(feature = "std")]
+fn raw_tax(price: Money, imported: Imported, category: &Category, policy: &TaxPolicy) -> i128 {
+    let kind = category.kind();
+    if policy.is_below_threshold(price) || kind == CategoryKind::Service {
+        return 0;
+    }
+    let rate = policy.basic_rate_for(kind)
+        + policy.excise_rate_for(kind)
+        + policy.import_rate_for(kind, imported);
+    i128::from(price.cents()) * (rate * 10_000.0).round() as i128
+}
+
+#[cfg(feature = "std")]
+impl Tax for Item {
+    /// Returns the clean price and the tax of the whole line: the tax is
+    /// rounded on a single unit and then multiplied by the quantity, or
+    /// rounded on the line for items sold by weight.
+    fn get_prices(&self) -> (Money, Money) {
+        self.get_prices_with(&TaxPolicy::default())
+    }
+    fn currency(&self) -> Option<Currency> {
+        self.currency
+    }
+}
+
+#[cfg(feature = "std")]
+impl Item {
+    /// Parses an item like [`Item::from_str`], but fails when none of the
+    /// default category keywords is in the description instead of falling
+    /// back to [`CategoryKind::Other`].
+    pub fn from_str_strict(s: &str) -> Result<Self, ParseItemError> {
+        let config = ParseConfig::default();
+        let item = Item::from_str_with(s, &config)?;
+        match config.rules.find(item.name()) {
+            Some(_) => Ok(item),
+            None => Err(ParseItemError::UnknownCategory),
+        }
+    }
+
+    /// Parses an item like [`Item::from_str`], detecting its category and
+    /// whether it is imported with `config`. The import marker may be
+    /// anywhere in the description: it is removed from the name, and the item
+    /// is always printed with "imported" first, e.g. "1 box of imported
+    /// chocolates" as "1 imported box of chocolates".
+    pub fn from_str_with(s: &str, config: &ParseConfig) -> Result<Self, ParseItemError> {
+        // The price follows the last " at ", the description may contain others.
+        let (item, price) = s.rsplit_once(" at ").ok_or(ParseItemError::MissingAt)?;
+        let (quantity, descr) = config.split_quantity(item)?;
+        let (price, currency) = parse_price(price, config.round_prices)?;
+        let mut words: Vec<&str> = descr.split_whitespace().collect();
+        let imported = if config.remove_import_marker(&mut words) {
+            Imported::Yes
+        } else {
+            Imported::No
+        };
+        let name = words.join(" ");
+        if name.is_empty() {
+            return Err(ParseItemError::MissingName);
+        }
+        let category = Category::new(config.rules.categorize(descr), name.clone());
+        let mut item = Item::with_quantity(quantity, price, imported, category)?;
+        item.currency = currency;
+        Ok(with_given_plural(item, name))
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for Item {
+    type Err = ParseItemError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Item::from_str_with(s, &ParseConfig::default())
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<&str> for Item {
+    type Error = ParseItemError;
+    /// Same as [`Item::from_str`].
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// How item descriptions are interpreted: the rules detecting their category,
+/// and the markers of imported items, e.g. "imported" or "from abroad".
+/// Markers match whole words only: "unimported" isn't "imported".
+/// Prices with more than two decimals, like "12.499", are rejected unless
+/// `round_prices` is set, in which case they are rounded half up to the cent.
+/// With `multiplier_quantities`, the count can also be written "3x book" or
+/// "book x3" instead of "3 book".
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseConfig {
+    pub rules: CategoryRules,
+    pub import_markers: Vec<String>,
+    pub round_prices: bool,
+    pub multiplier_quantities: bool,
+}
+
+#[cfg(feature = "std")]
+impl Default for ParseConfig {
+    /// The kata rules, with "imported" as the only import marker and strict
+    /// prices.
+    fn default() -> Self {
+        Self {
+            rules: CategoryRules::default(),
+            import_markers: vec!["imported".to_string()],
+            round_prices: false,
+            multiplier_quantities: false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ParseConfig {
+    /// The kata rules, same as the default.
+    pub fn english() -> Self {
+        Self::default()
+    }
+
+    /// German keywords and import markers, e.g. "1 importierte Tafel
+    /// Schokolade at 2.00". The layout of the line stays the same.
+    pub fn german() -> Self {
+        Self {
+            rules: CategoryRules::german(),
+            import_markers: ["importiert", "importierte", "importierter", "importiertes"]
+                .map(String::from)
+                .to_vec(),
+            round_prices: false,
+            multiplier_quantities: false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<CategoryRules> for ParseConfig {
+    fn from(rules: CategoryRules) -> Self {
+        Self {
+            rules,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ParseConfig {
+    /// Splits the part of a line before " at " into its quantity and its
+    /// description.
+    fn split_quantity<'a>(&self, s: &'a str) -> Result<(Quantity, &'a str), ParseItemError> {
+        let count = |s: &str| {
+            (!s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| s.parse().ok().map(Quantity::Count))
+                .flatten()
+        };
+        if self.multiplier_quantities {
+            let prefix = s.split_once(' ').and_then(|(quantity, descr)| {
+                let quantity = quantity.strip_suffix(['x', 'X'])?;
+                Some((count(quantity)?, descr))
+            });
+            let suffix = || {
+                s.rsplit_once(' ').and_then(|(descr, quantity)| {
+                    let quantity = quantity.strip_prefix(['x', 'X'])?;
+                    Some((count(quantity)?, descr))
+                })
+            };
+            if let Some(split) = prefix.or_else(suffix) {
+                return Ok(split);
+            }
+        }
+        let (quantity, descr) = s.split_once(' ').ok_or(ParseItemError::MissingQuantity)?;
+        Ok((quantity.parse()?, descr))
+    }
+
+    /// Removes the first import marker found in `words`, returning whether
+    /// there was one.
+    fn remove_import_marker(&self, words: &mut Vec<&str>) -> bool {
+        for marker in &self.import_markers {
+            let marker: Vec<&str> = marker.split_whitespace().collect();
+            if marker.is_empty() {
+                continue;
+            }
+            if let Some(index) = words
+                .windows(marker.len())
+                .position(|window| window == marker)
+            {
+                words.drain(index..index + marker.len());
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// The reasons a basket can reject its elements, or a payment.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BasketError {
+    MixedCurrencies,
+    InsufficientPayment,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for BasketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BasketError::MixedCurrencies => f.write_str("items must share the same currency"),
+            BasketError::InsufficientPayment => f.write_str("payment is below the total"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for BasketError {}
+
+/// Deserializing a basket goes through [`Basket::new`], so its elements must
+/// share the same currency.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "RawBasket<T>")
+)]
+pub struct Basket<T: Tax + ToString> {
+    elements: Vec<T>,
+}
+
+/// The fields of a serialized [`Basket`], before they are checked.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawBasket<T> {
+    elements: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<RawBasket<T>> for Basket<T>
+where
+    T: Tax + ToString,
+{
+    type Error = BasketError;
+    fn try_from(raw: RawBasket<T>) -> Result<Self, Self::Error> {
+        Basket::new(raw.elements)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Basket<T>
+where
+    T: Tax + ToString,
+{
+    /// Builds a basket, failing if its elements don't share the same currency.
+    pub fn new(elements: Vec<T>) -> Result<Self, BasketError> {
+        let currency = elements.first().map(Tax::currency);
+        if elements.iter().any(|x| Some(x.currency()) != currency) {
+            return Err(BasketError::MixedCurrencies);
+        }
+        Ok(Self { elements })
+    }
+    /// An empty basket with room for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            elements: Vec::with_capacity(capacity),
+        }
+    }
+    /// Reserves room for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.elements.reserve(additional);
+    }
+    /// Removes every element, keeping the allocation for reuse.
+    pub fn clear(&mut self) {
+        self.elements.clear();
+    }
+    /// Adds an element, failing if its currency differs from the basket's one.
+    pub fn add(&mut self, element: T) -> Result<(), BasketError> {
+        if !self.elements.is_empty() && element.currency() != self.currency() {
+            return Err(BasketError::MixedCurrencies);
+        }
+        self.elements.push(element);
+        Ok(())
+    }
+    pub fn currency(&self) -> Option<Currency> {
+        self.elements.first().and_then(Tax::currency)
+    }
+    /// Appends the elements of `other`, failing if the currencies differ.
+    pub fn merge(mut self, other: Basket<T>) -> Result<Basket<T>, BasketError> {
+        self.elements.extend(other.elements);
+        Basket::new(self.elements)
+    }
+    /// Groups the elements by currency, keeping their order, so that every
+    /// basket can be totaled on its own. Elements without a currency go under
+    /// `None`.
+    pub fn split_by_currency(self) -> HashMap<Option<Currency>, Basket<T>> {
+        let mut baskets: HashMap<Option<Currency>, Basket<T>> = HashMap::new();
+        for element in self.elements {
+            baskets
+                .entry(element.currency())
+                .or_default()
+                .elements
+                .push(element);
+        }
+        baskets
+    }
+    /// Removes the element at `index`, or returns `None` if it is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index < self.elements.len() {
+            Some(self.elements.remove(index))
+        } else {
+            None
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.elements.iter()
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.elements.iter_mut()
+    }
+    /// The number of elements (receipt lines), regardless of their quantity.
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+    /// The sums of the clean prices and of the taxes, computing the prices
+    /// of each element only once. Taxes are rounded per element whatever the
+    /// [`RoundScope`]: a [`Calculator`] rounds them once for the basket.
+    pub fn get_prices(&self) -> (Money, Money) {
+        let prices: Prices = self.elements.iter().map(Tax::get_prices).sum();
+        (prices.clean, prices.tax)
+    }
+    /// The element with the lowest price taxes included, the first one on ties.
+    pub fn cheapest(&self) -> Option<&T> {
+        self.elements
+            .iter()
+            .min_by_key(|element| gross_price(*element))
+    }
+
+    /// The element with the highest price taxes included, the first one on ties.
+    pub fn most_expensive(&self) -> Option<&T> {
+        self.elements
+            .iter()
+            .rev()
+            .max_by_key(|element| gross_price(*element))
+    }
+
+    /// The price to pay, taxes included, with the taxes of
+    /// [`Basket::get_prices`].
+    pub fn get_total(&self) -> Money {
+        let (clean_price, tax) = self.get_prices();
+        clean_price + tax
+    }
+    /// The change to give back when `tendered` is paid for the basket.
+    pub fn change(&self, tendered: Money) -> Result<Money, BasketError> {
+        let total = self.get_total();
+        if tendered < total {
+            return Err(BasketError::InsufficientPayment);
+        }
+        Ok(tendered - total)
+    }
+    /// The sum of the clean prices, i.e. the total without taxes.
+    pub fn get_subtotal(&self) -> Money {
+        self.get_prices().0
+    }
+    /// The sum of the taxes, rounded per element as in [`Basket::get_prices`].
+    pub fn get_tax(&self) -> Money {
+        self.get_prices().1
+    }
+}
+
+#[cfg(feature = "std")]
+fn gross_price<T: Tax>(element: &T) -> Money {
+    let (clean_price, tax) = element.get_prices();
+    clean_price + tax
+}
+
+#[cfg(feature = "std")]
+impl<T> Default for Basket<T>
+where
+    T: Tax + ToString,
+{
+    /// An empty basket.
+    fn default() -> Self {
+        Self {
+            elements: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Basket<T>
+where
+    T: Tax + ToString,
+{
+    /// Collects elements into a basket, failing like [`Basket::new`] if
+    /// their currencies differ.
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(iter: I) -> Result<Self, BasketError> {
+        Basket::new(iter.into_iter().collect())
+    }
+
+    /// Appends elements, failing like [`Basket::add`] if their currencies
+    /// differ from the basket's one; the basket is left unchanged on error.
+    pub fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), BasketError> {
+        let elements: Vec<T> = iter.into_iter().collect();
+        let currency = self
+            .elements
+            .first()
+            .or(elements.first())
+            .map(Tax::currency);
+        if elements.iter().any(|x| Some(x.currency()) != currency) {
+            return Err(BasketError::MixedCurrencies);
+        }
+        self.elements.extend(elements);
+        Ok(())
+    }
+}
+
+/// Collects elements into a basket, see [`Basket::try_from_iter`].
+///
+/// # Panics
+///
+/// If the currencies of the elements differ.
+#[cfg(feature = "std")]
+impl<T> FromIterator<T> for Basket<T>
+where
+    T: Tax + ToString,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::try_from_iter(iter).unwrap_or_else(|error| panic!("invalid basket: {}", error))
+    }
+}
+
+/// Appends elements, see [`Basket::try_extend`].
+///
+/// # Panics
+///
+/// If the currencies of the elements differ from the basket's one.
+#[cfg(feature = "std")]
+impl<T> Extend<T> for Basket<T>
+where
+    T: Tax + ToString,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.try_extend(iter)
+            .unwrap_or_else(|error| panic!("invalid basket: {}", error));
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> IntoIterator for Basket<T>
+where
+    T: Tax + ToString,
+{
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Display for Basket<T>
+where
+    T: Tax + ToString,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for element in &self.elements {
+            writeln!(f, "{}", element.to_string())?;
+        }
+        let symbol = self.currency().map_or("", |currency| currency.symbol());
+        let (clean_price, tax) = self.get_prices();
+        writeln!(f, "Sales Taxes: {}{}", symbol, tax)?;
+        write!(f, "Total: {}{}", symbol, clean_price + tax)
+    }
+}
+
+/// The order of the lines of a sorted receipt. Prices are the line totals,
+/// taxes included; items with the same key keep their basket order.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    PriceAscending,
+    PriceDescending,
+    Name,
+}
+
+#[cfg(feature = "std")]
+impl Basket<Item> {
+    /// A new basket with only the items of the `kind` category, e.g. to print
+    /// the receipt of the food alone.
+    pub fn filter_by_category(&self, kind: CategoryKind) -> Basket<Item> {
+        let elements = self
+            .elements
+            .iter()
+            .filter(|item| item.category.kind() == kind)
+            .cloned()
+            .collect();
+        Basket { elements }
+    }
+
+    /// Warns about the items taxed as [`CategoryKind::Other`] whose
+    /// description looks like a misspelled default keyword, e.g. "box of
+    /// chocolat", which would otherwise be exempt.
+    pub fn lint(&self) -> Vec<Warning> {
+        let rules = CategoryRules::default();
+        self.elements
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.category.kind() == CategoryKind::Other)
+            .filter_map(|(index, item)| {
+                let (keyword, kind) = rules.near_miss(item.name())?;
+                Some(Warning {
+                    index,
+                    name: item.name().to_string(),
+                    keyword: keyword.to_string(),
+                    kind,
+                })
+            })
+            .collect()
+    }
+
+    /// The sales taxes of the basket by category kind, for the kinds of its
+    /// items only.
+    pub fn tax_by_category(&self) -> HashMap<CategoryKind, Money> {
+        let mut taxes: HashMap<CategoryKind, Money> = HashMap::new();
+        for item in &self.elements {
+            let tax = taxes.entry(item.category.kind()).or_default();
+            *tax = *tax + item.get_prices().1;
+        }
+        taxes
+    }
+
+    /// The clean prices of the basket summed by category kind, for the kinds
+    /// of its items only; see [`Basket::tax_by_category`] for the taxes.
+    pub fn subtotal_by_category(&self) -> HashMap<CategoryKind, Money> {
+        let mut subtotals: HashMap<CategoryKind, Money> = HashMap::new();
+        for item in &self.elements {
+            let subtotal = subtotals.entry(item.category.kind()).or_default();
+            *subtotal = *subtotal + item.get_prices().0;
+        }
+        subtotals
+    }
+
+    /// Reduces the clean price of every item by `fraction`, between 0 and 1;
+    /// taxes are then computed on the reduced prices. Discounts already set
+    /// on the items still apply on top of it.
+    pub fn apply_discount(&mut self, fraction: f64) -> Result<(), ParseItemError> {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(ParseItemError::InvalidDiscount);
+        }
+        for item in &mut self.elements {
+            item.clean_price = discounted(item.clean_price, fraction);
+        }
+        Ok(())
+    }
+
+    /// The number of units in the basket, summing the quantity of every item.
+    /// An item sold by weight counts as one unit.
+    pub fn total_items(&self) -> i32 {
+        self.elements.iter().map(|item| units(item.quantity)).sum()
+    }
+
+    /// The taxable base: the sum of the clean prices of the items that are
+    /// actually taxed, exempt items being left out.
+    pub fn taxable_base(&self) -> Money {
+        self.elements
+            .iter()
+            .map(Tax::get_prices)
+            .filter(|(_, tax)| *tax != Money::ZERO)
+            .map(|(clean_price, _)| clean_price)
+            .sum()
+    }
+
+    /// The total with the taxes of `policy` instead of the default one, see
+    /// [`Calculator::total`].
+    pub fn total_with(&self, policy: &TaxPolicy) -> Money {
+        Calculator::new(policy.clone()).total(self)
+    }
+
+    /// The sales taxes of `policy` instead of the default one, see
+    /// [`Calculator::prices`].
+    pub fn tax_with(&self, policy: &TaxPolicy) -> Money {
+        Calculator::new(policy.clone()).prices(self).1
+    }
+
+    /// The receipt on a single line, e.g. for logs: "4 items, tax 6.70,
+    /// total 74.68".
+    pub fn summary_line(&self) -> String {
+        let symbol = self.currency().map_or("", |currency| currency.symbol());
+        let items = self.total_items();
+        format!(
+            "{} item{}, tax {}{}, total {}{}",
+            items,
+            if items.unsigned_abs() == 1 { "" } else { "s" },
+            symbol,
+            self.get_tax(),
+            symbol,
+            self.get_total()
+        )
+    }
+
+    /// Same as `to_string`, with an "Items: N" line before the sales taxes.
+    pub fn to_string_with_summary(&self) -> String {
+        self.receipt().to_string_with_summary()
+    }
+
+    /// Same as `to_string`, with the tax of every line, see
+    /// [`Receipt::to_string_verbose`].
+    pub fn to_string_verbose(&self) -> String {
+        self.receipt().to_string_verbose()
+    }
+
+    /// Same as `to_string`, but items with the same name, import status and
+    /// price are collapsed into a single line with their quantities summed.
+    /// Items sold by weight keep their own lines: their taxes are rounded
+    /// per line, so a merged line wouldn't add up to the same totals.
+    pub fn to_string_grouped(&self) -> String {
+        let mut groups: Vec<Item> = Vec::new();
+        for item in &self.elements {
+            // Returns aren't netted out against purchases, and a group whose
+            // count would overflow is left as it is.
+            let grouped = groups
+                .iter_mut()
+                .filter(|group| group.same_product(item))
+                .find_map(|group| {
+                    group.quantity = match (group.quantity, item.quantity) {
+                        (Quantity::Count(a), Quantity::Count(b)) if a.signum() == b.signum() => {
+                            Quantity::Count(a.checked_add(b)?)
+                        }
+                        _ => return None,
+                    };
+                    Some(())
+                });
+            if grouped.is_none() {
+                groups.push(item.clone());
+            }
+        }
+        Basket { elements: groups }.to_string()
+    }
+
+    /// Same as `to_string`, with amounts printed using `format`.
+    pub fn to_string_with(&self, format: &NumberFormat) -> String {
+        self.receipt().to_string_with(format)
+    }
+
+    /// The computed lines of the receipt, see [`Basket::receipt`].
+    pub fn lines(&self) -> Vec<ReceiptLine> {
+        self.receipt().lines
+    }
+
+    /// The receipt as an HTML table, see [`Receipt::to_html`].
+    pub fn to_html(&self) -> String {
+        self.receipt().to_html()
+    }
+
+    /// The receipt as a Markdown table, see [`Receipt::to_markdown`].
+    pub fn to_markdown(&self) -> String {
+        self.receipt().to_markdown()
+    }
+
+    /// The lines and totals of the receipt, ready to be rendered.
+    pub fn receipt(&self) -> Receipt {
+        Calculator::default().receipt(self)
+    }
+
+    /// Same as `to_string`, with the item lines sorted by `order`.
+    pub fn to_string_sorted(&self, order: SortOrder) -> String {
+        let mut elements = self.elements.clone();
+        let line_total = |item: &Item| {
+            let (clean_price, tax) = item.get_prices();
+            clean_price + tax
+        };
+        match order {
+            SortOrder::PriceAscending => elements.sort_by_key(line_total),
+            SortOrder::PriceDescending => {
+                elements.sort_by_key(|item| std::cmp::Reverse(line_total(item)))
+            }
+            SortOrder::Name => elements.sort_by(|a, b| a.name().cmp(b.name())),
+        }
+        Basket { elements }.to_string()
+    }
+
+    /// Same as `to_string`, with the item lines grouped by category in the
+    /// order of [`CategoryKind`]; items of a category keep their basket order.
+    pub fn to_string_by_category(&self) -> String {
+        let mut elements = self.elements.clone();
+        elements.sort_by_key(|item| item.category.kind());
+        Basket { elements }.to_string()
+    }
+}
+
+/// A likely miscategorized item of a basket, see [`Basket::lint`]: the item
+/// at `index` is taxed as other goods, but its name looks like `keyword`, of
+/// the `kind` category.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub index: usize,
+    pub name: String,
+    pub keyword: String,
+    pub kind: CategoryKind,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "item {} ({:?}) is taxed as other goods, did you mean {:?}?",
+            self.index, self.name, self.keyword
+        )
+    }
+}
+
+/// Several items sold together under one name, e.g. a meal deal, printed as
+/// a single line "1 meal deal: 15.00". The prices are the sums of the ones of
+/// the items, so a `Basket<BundleItem>` totals like the items themselves.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BundleItem {
+    pub name: String,
+    pub items: Basket<Item>,
+}
+
+#[cfg(feature = "std")]
+impl BundleItem {
+    pub fn new(name: impl Into<String>, items: Basket<Item>) -> Self {
+        Self {
+            name: name.into(),
+            items,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Tax for BundleItem {
+    fn get_prices(&self) -> (Money, Money) {
+        self.items.get_prices()
+    }
+    fn currency(&self) -> Option<Currency> {
+        self.items.currency()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for BundleItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = self.currency().map_or("", |currency| currency.symbol());
+        write!(f, "1 {}: {}{}", self.name, symbol, self.items.get_total())
+    }
+}
+
+/// The entry point of the library: computes prices, taxes and receipts with
+/// a single [`TaxPolicy`], instead of passing it to every call. The default
+/// calculator follows the rules of the kata.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Calculator {
+    policy: TaxPolicy,
+}
+
+#[cfg(feature = "std")]
+impl Calculator {
+    pub fn new(policy: TaxPolicy) -> Self {
+        Self { policy }
+    }
+
+    pub fn policy(&self) -> &TaxPolicy {
+        &self.policy
+    }
+
+    /// The clean price and the tax of the item, see [`Item::get_prices_with`].
+    pub fn price(&self, item: &Item) -> (Money, Money) {
+        item.get_prices_with(&self.policy)
+    }
+
+    pub fn tax_breakdown(&self, item: &Item) -> TaxBreakdown {
+        item.tax_breakdown_with(&self.policy)
+    }
+
+    /// The subtotal and the tax of the whole basket. With
+    /// [`RoundScope::Basket`] the tax is rounded once on the sum of the
+    /// unrounded taxes of the items.
+    pub fn prices(&self, basket: &Basket<Item>) -> (Money, Money) {
+        let prices: Prices = basket.iter().map(|item| self.price(item)).sum();
+        let (clean_price, tax) = (prices.clean, prices.tax);
+        match self.policy.round_per {
+            RoundScope::Item => (clean_price, tax),
+            RoundScope::Basket => {
+                let raw = basket
+                    .iter()
+                    .map(|item| item.raw_tax_with(&self.policy))
+                    .sum();
+                let tax = round_raw(raw, self.policy.rounding_increment, self.policy.rounding);
+                (clean_price, tax)
+            }
+        }
+    }
+
+    pub fn total(&self, basket: &Basket<Item>) -> Money {
+        let (clean_price, tax) = self.prices(basket);
+        clean_price + tax
+    }
+
+    pub fn receipt(&self, basket: &Basket<Item>) -> Receipt {
+        let (clean_price, tax) = self.prices(basket);
+        Receipt {
+            lines: basket
+                .iter()
+                .map(|item| item.receipt_line(&self.policy))
+                .collect(),
+            currency: basket.currency(),
+            sales_taxes: tax,
+            total: clean_price + tax,
+        }
+    }
+}
+
+/// The content of a receipt, separated from its formatting: `Display`
+/// prints it as the kata does.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Receipt {
+    #[cfg_attr(feature = "serde", serde(rename = "items"))]
+    pub lines: Vec<ReceiptLine>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub currency: Option<Currency>,
+    pub sales_taxes: Money,
+    pub total: Money,
+}
+
+/// One item of a [`Receipt`]. The name is the one printed, plural when the
+/// quantity is above one. `unit_clean` is the clean price of one unit, after
+/// discounts; the unit tax is the tax of one unit, or of the whole line for
+/// items sold by weight. The tax and the total are the ones of the line, and
+/// the tax rate is the effective one: the tax over the clean price of the
+/// line, zero when that price is.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ReceiptLine {
+    pub name: String,
+    pub imported: Imported,
+    pub quantity: Quantity,
+    pub unit_clean: Money,
+    pub unit_tax: Money,
+    pub tax: Money,
+    pub tax_rate: f64,
+    pub line_total: Money,
+}
+
+#[cfg(feature = "std")]
+impl ReceiptLine {
+    fn to_string_with(&self, symbol: &str, format: &NumberFormat) -> String {
+        format!(
+            "{} {}: {}{}",
+            self.quantity,
+            self.label(),
+            symbol,
+            self.line_total.format_with(format)
+        )
+    }
+
+    /// The name as printed, which says whether the item is imported: after
+    /// the unit for goods sold by weight, as in "2.5 kg of imported nails".
+    fn label(&self) -> String {
+        if !matches!(self.imported, Imported::Yes) {
+            return self.name.clone();
+        }
+        match (self.quantity, self.name.split_once(" of ")) {
+            (Quantity::Weight(_), Some((unit, product))) => {
+                format!("{} of imported {}", unit, product)
+            }
+            _ => format!("imported {}", self.name),
+        }
+    }
+}
+
+/// The labels of the receipt footer, e.g. to print it in another language.
+/// The lines with the number of units and the effective tax rate are only
+/// printed when `items` and `tax_rate` are set, the default labels are the
+/// English ones of `to_string`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptLabels {
+    pub items: Option<String>,
+    pub sales_taxes: String,
+    pub total: String,
+    pub tax_rate: Option<String>,
+}
+
+#[cfg(feature = "std")]
+impl Default for ReceiptLabels {
+    fn default() -> Self {
+        Self {
+            items: None,
+            sales_taxes: "Sales Taxes:".to_string(),
+            total: "Total:".to_string(),
+            tax_rate: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Receipt {
+    /// Same as `to_string`, with amounts printed using `format`.
+    pub fn to_string_with(&self, format: &NumberFormat) -> String {
+        self.render(format, &ReceiptLabels::default(), false)
+    }
+
+    /// Same as `to_string`, with the footer labels of `labels`.
+    pub fn to_string_with_labels(&self, labels: &ReceiptLabels) -> String {
+        self.render(&NumberFormat::default(), labels, false)
+    }
+
+    /// Same as `to_string`, with an "Items: N" line before the sales taxes,
+    /// N being the number of units bought.
+    pub fn to_string_with_summary(&self) -> String {
+        let labels = ReceiptLabels {
+            items: Some("Items:".to_string()),
+            ..ReceiptLabels::default()
+        };
+        self.render(&NumberFormat::default(), &labels, false)
+    }
+
+    /// Same as `to_string`, with the tax of every line after its total, e.g.
+    /// "1 imported bottle of perfume: 32.19 (tax 4.20)", and a last
+    /// "Effective Tax Rate: 9.86%" line, the sales taxes over the subtotal.
+    pub fn to_string_verbose(&self) -> String {
+        let labels = ReceiptLabels {
+            tax_rate: Some("Effective Tax Rate:".to_string()),
+            ..ReceiptLabels::default()
+        };
+        self.render(&NumberFormat::default(), &labels, true)
+    }
+
+    /// The number of units on the receipt, an item sold by weight counting
+    /// as one.
+    pub fn total_items(&self) -> i32 {
+        self.lines.iter().map(|line| units(line.quantity)).sum()
+    }
+
+    fn render(&self, format: &NumberFormat, labels: &ReceiptLabels, verbose: bool) -> String {
+        let symbol = self.currency.map_or("", |currency| currency.symbol());
+        let mut lines: Vec<String> = self
+            .lines
+            .iter()
+            .map(|line| {
+                let text = line.to_string_with(symbol, format);
+                if verbose {
+                    format!("{} (tax {}{})", text, symbol, line.tax.format_with(format))
+                } else {
+                    text
+                }
+            })
+            .collect();
+        if let Some(items) = &labels.items {
+            lines.push(format!("{} {}", items, self.total_items()));
+        }
+        lines.push(format!(
+            "{} {}{}",
+            labels.sales_taxes,
+            symbol,
+            self.sales_taxes.format_with(format)
+        ));
+        lines.push(format!(
+            "{} {}{}",
+            labels.total,
+            symbol,
+            self.total.format_with(format)
+        ));
+        if let Some(tax_rate) = &labels.tax_rate {
+            let subtotal = self.total - self.sales_taxes;
+            let percent = if subtotal == Money::ZERO {
+                0.0
+            } else {
+                100.0 * self.sales_taxes.cents() as f64 / subtotal.cents() as f64
+            };
+            let percent =
+                format!("{:.2}", percent).replace('.', &format.decimal_separator.to_string());
+            lines.push(format!("{} {}%", tax_rate, percent));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Receipt {
+    /// The receipt as an HTML `<table>`, one row per line and the totals in
+    /// the footer. Names are escaped.
+    pub fn to_html(&self) -> String {
+        let symbol = self.currency.map_or("", |currency| currency.symbol());
+        let mut html = String::from("<table>\n<tbody>\n");
+        for line in &self.lines {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}{}</td></tr>\n",
+                line.quantity,
+                escape_html(&line.label()),
+                symbol,
+                line.line_total
+            ));
+        }
+        html.push_str("</tbody>\n<tfoot>\n");
+        html.push_str(&format!(
+            "<tr><td colspan=\"2\">Sales Taxes</td><td>{}{}</td></tr>\n",
+            symbol, self.sales_taxes
+        ));
+        html.push_str(&format!(
+            "<tr><td colspan=\"2\">Total</td><td>{}{}</td></tr>\n",
+            symbol, self.total
+        ));
+        html.push_str("</tfoot>\n</table>");
+        html
+    }
+
+    /// The receipt as a GitHub-flavored Markdown table with the quantity,
+    /// the price taxes included and the tax of every line, and a last row
+    /// with the totals. Pipes in names are escaped.
+    pub fn to_markdown(&self) -> String {
+        let symbol = self.currency.map_or("", |currency| currency.symbol());
+        let mut markdown = String::from("| Item | Qty | Price | Tax |\n|:---|---:|---:|---:|\n");
+        for line in &self.lines {
+            markdown.push_str(&format!(
+                "| {} | {} | {}{} | {}{} |\n",
+                line.label().replace('|', "\\|"),
+                line.quantity,
+                symbol,
+                line.line_total,
+                symbol,
+                line.tax
+            ));
+        }
+        markdown.push_str(&format!(
+            "| **Total** | {} | {}{} | {}{} |",
+            self.total_items(),
+            symbol,
+            self.total,
+            symbol,
+            self.sales_taxes
+        ));
+        markdown
+    }
+}
+
+#[cfg(feature = "std")]
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Receipt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string_with(&NumberFormat::default()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Basket<Item> {
+    /// The receipt as a JSON document, with the same numbers as `to_string`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.receipt()).expect("a receipt is always valid JSON")
+    }
+}
+
+/// An error while parsing a basket, with the 1-based line it happened on
+/// and the text of that line, trimmed.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasketParseError {
+    pub line: usize,
+    pub text: String,
+    pub source: ParseItemError,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for BasketParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} ({:?}): {}", self.line, self.text, self.source)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for BasketParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for Basket<Item> {
+    type Err = BasketParseError;
+    /// Parses one item per line, skipping blank lines and comments, the
+    /// lines starting with `#`. Items must share the same currency, as in
+    /// [`Basket::new`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut basket = Basket::default();
+        for (index, line) in s.lines().enumerate() {
+            let text = line.trim();
+            if !text.is_empty() && !text.starts_with('#') {
+                push_line(&mut basket, index + 1, text)?;
+            }
+        }
+        Ok(basket)
+    }
+}
+
+/// Parses `text`, the line number `line` of a basket, and adds its item to
+/// `basket`.
+#[cfg(feature = "std")]
+fn push_line(basket: &mut Basket<Item>, line: usize, text: &str) -> Result<(), BasketParseError> {
+    let error = |source| BasketParseError {
+        line,
+        text: text.to_string(),
+        source,
+    };
+    let item = Item::from_str(text).map_err(error)?;
+    basket
+        .add(item)
+        .map_err(|_| error(ParseItemError::MixedCurrencies))
+}
+
+/// Parses several baskets in the format of [`Basket::from_str`], separated
+/// by blank lines or `---` lines. Error lines are counted from the start of
+/// `input`.
+#[cfg(feature = "std")]
+pub fn parse_baskets(input: &str) -> Result<Vec<Basket<Item>>, BasketParseError> {
+    let mut baskets = Vec::new();
+    let mut basket = Basket::default();
+    for (index, line) in input.lines().enumerate() {
+        let text = line.trim();
+        if text.is_empty() || text == "---" {
+            if !basket.is_empty() {
+                baskets.push(std::mem::take(&mut basket));
+            }
+        } else if !text.starts_with('#') {
+            push_line(&mut basket, index + 1, text)?;
+        }
+    }
+    if !basket.is_empty() {
+        baskets.push(basket);
+    }
+    Ok(baskets)
+}
+
+/// An error while reading items with [`parse_items`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum ReadError {
+    /// The input could not be read, or isn't valid UTF-8.
+    Read(std::io::Error),
+    /// A line doesn't describe a valid item.
+    Item(BasketParseError),
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Read(error) => write!(f, "cannot read the items: {}", error),
+            ReadError::Item(error) => error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Read(error) => Some(error),
+            ReadError::Item(error) => Some(error),
+        }
+    }
+}
+
+/// Parses the items of `reader` lazily, one line at a time, in the format of
+/// [`Basket::from_str`], so that large inputs can be totaled without being
+/// held in memory. Reading goes on after an invalid line.
+///
+/// Errors are [`ReadError`]s rather than [`ParseItemError`]s: a line can fail
+/// to be read from `reader` as well as to be parsed, and a parse error keeps
+/// the number and the text of its line, as in [`BasketParseError`].
+#[cfg(feature = "std")]
+pub fn parse_items<R: std::io::BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<Item, ReadError>> {
+    reader.lines().enumerate().filter_map(|(index, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(error) => return Some(Err(ReadError::Read(error))),
+        };
+        let text = line.trim();
+        if text.is_empty() || text.starts_with('#') {
+            return None;
+        }
+        Some(Item::from_str(text).map_err(|source| {
+            ReadError::Item(BasketParseError {
+                line: index + 1,
+                text: text.to_string(),
+                source,
+            })
+        }))
+    })
+}
+
+/// An error while reading a basket from CSV.
+#[cfg(feature = "csv")]
+#[derive(Debug)]
+pub enum CsvError {
+    /// The data is not valid CSV or could not be read.
+    Read(csv::Error),
+    /// A record doesn't describe a valid item.
+    Item(BasketParseError),
+}
+
+#[cfg(feature = "csv")]
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CsvError::Read(error) => write!(f, "invalid CSV: {}", error),
+            CsvError::Item(error) => error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "csv")]
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvError::Read(error) => Some(error),
+            CsvError::Item(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "csv")]
+impl Basket<Item> {
+    /// Reads a basket from CSV with a header row and the columns quantity,
+    /// description, imported ("yes"/"no" or "true"/"false", empty means no)
+    /// and price. Categories are detected from the description as in
+    /// [`Item::from_str`].
+    pub fn from_csv(reader: impl std::io::Read) -> Result<Basket<Item>, CsvError> {
+        let rules = CategoryRules::default();
+        let mut basket = Basket::default();
+        for record in csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader)
+            .records()
+        {
+            let record = record.map_err(CsvError::Read)?;
+            let line = record
+                .position()
+                .map_or(0, |position| position.line() as usize);
+            let error = |source| {
+                CsvError::Item(BasketParseError {
+                    line,
+                    text: record.iter().collect::<Vec<_>>().join(","),
+                    source,
+                })
+            };
+            let item = Self::csv_item(&record, &rules).map_err(error)?;
+            basket
+                .add(item)
+                .map_err(|_| error(ParseItemError::MixedCurrencies))?;
+        }
+        Ok(basket)
+    }
+
+    fn csv_item(record: &csv::StringRecord, rules: &CategoryRules) -> Result<Item, ParseItemError> {
+        let quantity: Quantity = match record.get(0) {
+            None | Some("") => return Err(ParseItemError::MissingQuantity),
+            Some(quantity) => quantity
+                .parse()
+                .map_err(|_| ParseItemError::InvalidQuantity)?,
+        };
+        let name = match record.get(1) {
+            None | Some("") => return Err(ParseItemError::MissingName),
+            Some(name) => name.split_whitespace().collect::<Vec<_>>().join(" "),
+        };
+        let imported = match record.get(2).map(str::to_ascii_lowercase).as_deref() {
+            None | Some("") | Some("no") | Some("false") => Imported::No,
+            Some("yes") | Some("true") => Imported::Yes,
+            Some(_) => return Err(ParseItemError::InvalidImported),
+        };
+        let price = match record.get(3) {
+            None | Some("") => return Err(ParseItemError::MissingPrice),
+            Some(price) => price.parse().map_err(|_| ParseItemError::InvalidPrice)?,
+        };
+        let category = Category::new(rules.categorize(&name), name.clone());
+        let item = Item::with_quantity(quantity, price, imported, category)?;
+        Ok(with_given_plural(item, name))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod money_tests {
+    use super::*;
+    #[test]
+    fn test_exact_equality() {
+        // 0.1 + 0.2 != 0.3 with floats, but the totals are exact cents.
+        let basket = Basket::<Item>::from_str(
+            "1 book at 0.10
+1 book at 0.20",
+        )
+        .unwrap();
+        assert_eq!(basket.get_total(), Money::from_str("0.30").unwrap());
+        let other = Basket::<Item>::from_str("1 book at 0.30").unwrap();
+        assert_eq!(basket.get_total(), other.get_total());
+        assert_ne!(basket.get_total(), Money::from_cents(29));
+    }
+    #[test]
+    fn test_parse_money() {
+        assert_eq!(Money::from_str("27.99").unwrap(), Money::from_cents(2799));
+        assert_eq!(Money::from_str("0.85").unwrap(), Money::from_cents(85));
+        assert_eq!(Money::from_str("12").unwrap(), Money::from_cents(1200));
+        assert_eq!(Money::from_str("1.5").unwrap(), Money::from_cents(150));
+        assert_eq!(Money::from_str("-18.99").unwrap(), Money::from_cents(-1899));
+    }
+    #[test]
+    fn test_parse_money_invalid() {
+        assert!(Money::from_str("").is_err());
+        assert!(Money::from_str("abc").is_err());
+        assert!(Money::from_str("12.").is_err());
+        assert!(Money::from_str(".5").is_err());
+        assert!(Money::from_str("12.499").is_err());
+        assert!(Money::from_str("1e3").is_err());
+    }
+    #[test]
+    fn test_display_money() {
+        assert_eq!(Money::from_cents(1249).to_string(), "12.49");
+        assert_eq!(Money::from_cents(150).to_string(), "1.50");
+        assert_eq!(Money::from_cents(5).to_string(), "0.05");
+        assert_eq!(Money::from_cents(-85).to_string(), "-0.85");
+    }
+    #[test]
+    fn test_sum_money() {
+        let total: Money = [85, 1249, 1499].into_iter().map(Money::from_cents).sum();
+        assert_eq!(total, Money::from_cents(2833));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod item_builder_tests {
+    use super::*;
+    #[test]
+    fn test_build_item() {
+        let item = Item::builder()
+            .name("bottle of perfume")
+            .price(Money::from_cents(4750))
+            .imported(Imported::Yes)
+            .category(CategoryKind::Other)
+            .quantity(2)
+            .build()
+            .unwrap();
+        assert_eq!(item.quantity, Quantity::Count(2));
+        assert_eq!(item.to_string(), "2 imported bottles of perfume: 109.30");
+    }
+    #[test]
+    fn test_build_defaults() {
+        let item = ItemBuilder::new()
+            .name("music CD")
+            .price(Money::from_cents(1499))
+            .build()
+            .unwrap();
+        assert_eq!(item.quantity, Quantity::Count(1));
+        assert_eq!(item.imported, Imported::No);
+        assert_eq!(item.category.kind(), CategoryKind::Other);
+        assert_eq!(item.to_string(), "1 music CD: 16.49");
+    }
+    #[test]
+    fn test_build_missing_fields() {
+        let missing_price = Item::builder().name("book").build();
+        assert_eq!(missing_price.unwrap_err(), ParseItemError::MissingPrice);
+        let missing_name = Item::builder().price(Money::from_cents(1249)).build();
+        assert_eq!(missing_name.unwrap_err(), ParseItemError::MissingName);
+    }
+    #[test]
+    fn test_build_validates() {
+        let zero = Item::builder()
+            .name("book")
+            .price(Money::from_cents(1249))
+            .quantity(0)
+            .build();
+        assert_eq!(zero.unwrap_err(), ParseItemError::InvalidQuantity);
+        let negative = Item::builder()
+            .name("book")
+            .price(Money::from_cents(-1249))
+            .build();
+        assert_eq!(negative.unwrap_err(), ParseItemError::NegativePrice);
+    }
+    #[test]
+    fn test_name() {
+        let item = Item::builder()
+            .name("packet of headache pills")
+            .category(CategoryKind::Medical)
+            .price(Money::from_cents(975))
+            .build()
+            .unwrap();
+        assert_eq!(item.name(), "packet of headache pills");
+        let parsed = Item::from_str("1 imported bottle of perfume at 27.99").unwrap();
+        assert_eq!(parsed.name(), "bottle of perfume");
+    }
+    #[test]
+    fn test_build_overflow() {
+        let at_limit = Item::builder()
+            .name("book")
+            .price(Money::from_cents(Item::MAX_LINE_TOTAL.cents() / 4))
+            .quantity(4)
+            .build();
+        assert!(at_limit.is_ok());
+        let above_limit = Item::builder()
+            .name("book")
+            .price(Money::from_cents(Item::MAX_LINE_TOTAL.cents() / 4 + 1))
+            .quantity(4)
+            .build();
+        assert_eq!(above_limit.unwrap_err(), ParseItemError::Overflow);
+        let overflow = Item::builder()
+            .name("book")
+            .price(Money::from_cents(i64::MAX))
+            .quantity(i32::MAX)
+            .build();
+        assert_eq!(overflow.unwrap_err(), ParseItemError::Overflow);
+        assert_eq!(
+            Item::from_str("2147483647 book at 92233720368547758.07").unwrap_err(),
+            ParseItemError::Overflow
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod item_hash_tests {
+    use super::*;
+    #[test]
+    fn test_deduplicate() {
+        let mut items = HashSet::new();
+        items.insert(Item::from_str("1 imported box of chocolates at 11.25").unwrap());
+        items.insert(Item::from_str("1 box of imported chocolates at 11.25").unwrap());
+        assert_eq!(items.len(), 1);
+        items.insert(Item::from_str("2 imported box of chocolates at 11.25").unwrap());
+        items.insert(Item::from_str("1 box of chocolates at 11.25").unwrap());
+        items.insert(
+            Item::from_str("1 imported box of chocolates at 11.25")
+                .unwrap()
+                .with_discount(0.1)
+                .unwrap(),
+        );
+        assert_eq!(items.len(), 4);
+    }
+    #[test]
+    fn test_signed_zero() {
+        let rice = |discount| {
+            Item::from_str("0.5 kg of rice at 2.00")
+                .unwrap()
+                .with_discount(discount)
+                .unwrap()
+        };
+        let items = HashSet::from([rice(0.0), rice(-0.0)]);
+        assert_eq!(items.len(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod gross_price_tests {
+    use super::*;
+    #[test]
+    fn test_from_gross_round_trips() {
+        let cases = [
+            (3219, Imported::Yes, CategoryKind::Other, 2799),
+            (2089, Imported::No, CategoryKind::Other, 1899),
+            (975, Imported::No, CategoryKind::Medical, 975),
+            (1185, Imported::Yes, CategoryKind::Food, 1125),
+            (5465, Imported::Yes, CategoryKind::Other, 4750),
+        ];
+        for (gross, imported, kind, clean) in cases {
+            let item = Item::from_gross(
+                Money::from_cents(gross),
+                imported,
+                Category::new(kind, "item"),
+            )
+            .unwrap();
+            assert_eq!(item.clean_price, Money::from_cents(clean));
+            let (clean_price, tax) = item.get_prices();
+            assert_eq!(clean_price + tax, Money::from_cents(gross));
+        }
+    }
+    #[test]
+    fn test_from_gross_ambiguity() {
+        let category = || Category::Other("pen".to_string());
+        let pen = Item::from_gross(Money::from_cents(55), Imported::No, category()).unwrap();
+        assert_eq!(pen.clean_price, Money::from_cents(50));
+        // No clean price costs 0.58, the item costs a bit less.
+        let pen = Item::from_gross(Money::from_cents(58), Imported::No, category()).unwrap();
+        assert_eq!(pen.clean_price, Money::from_cents(50));
+        let (clean_price, tax) = pen.get_prices();
+        assert_eq!(clean_price + tax, Money::from_cents(55));
+        assert_eq!(
+            Item::from_gross(Money::from_cents(-1), Imported::No, category()).unwrap_err(),
+            ParseItemError::NegativePrice
+        );
+        let free = Item::from_gross(Money::ZERO, Imported::No, category()).unwrap();
+        assert_eq!(free.clean_price, Money::ZERO);
+    }
+    #[test]
+    fn test_from_gross_overflow() {
+        let category = || Category::Other("pen".to_string());
+        assert!(Item::from_gross(Item::MAX_LINE_TOTAL, Imported::Yes, category()).is_ok());
+        assert_eq!(
+            Item::from_gross(Money::from_cents(i64::MAX), Imported::No, category()).unwrap_err(),
+            ParseItemError::Overflow
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod discount_tests {
+    use super::*;
+    #[test]
+    fn test_discount_before_tax() {
+        let perfume = Item::from_str("1 imported bottle of perfume at 47.50")
+            .unwrap()
+            .with_discount(0.20)
+            .unwrap();
+        let (clean_price, tax) = perfume.get_prices();
+        assert_eq!(clean_price, Money::from_cents(3800));
+        assert_eq!(tax, Money::from_cents(570));
+        assert_eq!(perfume.to_string(), "1 imported bottle of perfume: 43.70");
+    }
+    #[test]
+    fn test_discount_rounds_to_cent() {
+        let music_cds = Item::from_str("2 music CDs at 14.99")
+            .unwrap()
+            .with_discount(0.5)
+            .unwrap();
+        // 7.495 off each unit is rounded to 7.50.
+        let (clean_price, tax) = music_cds.get_prices();
+        assert_eq!(clean_price, Money::from_cents(1498));
+        assert_eq!(tax, Money::from_cents(150));
+    }
+    #[test]
+    fn test_discount_bounds() {
+        let book = || Item::from_str("1 book at 12.49").unwrap();
+        assert_eq!(
+            book().with_discount(0.0).unwrap().get_prices().0,
+            Money::from_cents(1249)
+        );
+        assert_eq!(
+            book().with_discount(1.0).unwrap().get_prices().0,
+            Money::ZERO
+        );
+        assert_eq!(
+            book().with_discount(1.5).unwrap_err(),
+            ParseItemError::InvalidDiscount
+        );
+        assert_eq!(
+            book().with_discount(-0.1).unwrap_err(),
+            ParseItemError::InvalidDiscount
+        );
+        assert_eq!(
+            book().with_discount(f64::NAN).unwrap_err(),
+            ParseItemError::InvalidDiscount
+        );
+    }
+    #[test]
+    fn test_builder_discount() {
+        let invalid = Item::builder()
+            .name("book")
+            .price(Money::from_cents(1249))
+            .discount(2.0)
+            .build();
+        assert_eq!(invalid.unwrap_err(), ParseItemError::InvalidDiscount);
+        let book = Item::builder()
+            .name("book")
+            .price(Money::from_cents(1000))
+            .category(CategoryKind::Book)
+            .discount(0.25)
+            .build()
+            .unwrap();
+        assert_eq!(book.to_string(), "1 book: 7.50");
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod pluralize_tests {
+    use super::*;
+    #[test]
+    fn test_pluralize() {
+        assert_eq!(pluralize("book"), "books");
+        assert_eq!(pluralize("music CD"), "music CDs");
+        assert_eq!(pluralize("bottle of perfume"), "bottles of perfume");
+        assert_eq!(pluralize("box of chocolates"), "boxes of chocolates");
+        assert_eq!(
+            pluralize("packet of headache pills"),
+            "packets of headache pills"
+        );
+        assert_eq!(pluralize("chocolate bar"), "chocolate bars");
+        assert_eq!(pluralize("battery"), "batteries");
+        assert_eq!(pluralize("toy"), "toys");
+    }
+    #[test]
+    fn test_display_plural() {
+        let books = Item::new(
+            3,
+            Money::from_cents(1249),
+            Imported::No,
+            Category::Book("book".to_string()),
+        )
+        .unwrap();
+        assert_eq!(books.to_string(), "3 books: 37.47");
+        let book = Item::new(
+            1,
+            Money::from_cents(1249),
+            Imported::No,
+            Category::Book("book".to_string()),
+        )
+        .unwrap();
+        assert_eq!(book.to_string(), "1 book: 12.49");
+    }
+    #[test]
+    fn test_parsed_plural() {
+        let books = Item::from_str("3 book at 12.49").unwrap();
+        assert_eq!(books.to_string(), "3 books: 37.47");
+        let books = Item::from_str("3 books at 12.49").unwrap();
+        assert_eq!(books.to_string(), "3 books: 37.47");
+        let cds = Item::from_str("2 music CD at 14.99").unwrap();
+        assert_eq!(cds.to_string(), "2 music CDs: 32.98");
+    }
+    #[test]
+    fn test_display_plural_override() {
+        let mice = Item::new(
+            2,
+            Money::from_cents(1000),
+            Imported::Yes,
+            Category::Other("computer mouse".to_string()),
+        )
+        .unwrap()
+        .with_plural("computer mice");
+        assert_eq!(mice.to_string(), "2 imported computer mice: 23.00");
+        let mouse = Item::new(
+            1,
+            Money::from_cents(1000),
+            Imported::Yes,
+            Category::Other("computer mouse".to_string()),
+        )
+        .unwrap()
+        .with_plural("computer mice");
+        assert_eq!(mouse.to_string(), "1 imported computer mouse: 11.50");
+    }
+    #[test]
+    fn test_parsed_plural_is_kept() {
+        let item = Item::from_str("3 imported bottles of perfume at 27.99").unwrap();
+        assert_eq!(item.to_string(), "3 imported bottles of perfume: 96.57");
+        let item = Item::from_str("2 books at 12.49").unwrap();
+        assert_eq!(item.to_string(), "2 books: 24.98");
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod number_format_tests {
+    use super::*;
+    #[test]
+    fn test_format_with() {
+        let european = NumberFormat::european();
+        assert_eq!(Money::from_cents(1249).format_with(&european), "12,49");
+        assert_eq!(
+            Money::from_cents(123_456).format_with(&european),
+            "1.234,56"
+        );
+        assert_eq!(
+            Money::from_cents(-123_456_789).format_with(&european),
+            "-1.234.567,89"
+        );
+        assert_eq!(
+            Money::from_cents(100_000).format_with(&european),
+            "1.000,00"
+        );
+        assert_eq!(
+            Money::from_cents(123_456).format_with(&NumberFormat::default()),
+            "1234.56"
+        );
+        let swiss = NumberFormat {
+            decimal_separator: '.',
+            thousands_separator: Some('\''),
+        };
+        assert_eq!(Money::from_cents(123_456).format_with(&swiss), "1'234.56");
+    }
+    #[test]
+    fn test_european_receipt() {
+        let basket = Basket::<Item>::from_str(
+            "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25",
+        )
+        .unwrap();
+        assert_eq!(
+            basket.to_string_with(&NumberFormat::european()),
+            "1 imported bottle of perfume: 32,19
+1 bottle of perfume: 20,89
+1 packet of headache pills: 9,75
+1 imported box of chocolates: 11,85
+Sales Taxes: 6,70
+Total: 74,68"
+        );
+        assert_eq!(
+            basket.to_string_with(&NumberFormat::default()),
+            basket.to_string()
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_book() {
+        let book = Item::new(
+            1,
+            Money::from_cents(1249),
+            Imported::No,
+            Category::Book("book".to_string()),
+        )
+        .unwrap();
+        let (clean_price, tax) = book.get_prices();
+        let expected = (Money::from_cents(1249), Money::from_cents(0));
+        assert_eq!(clean_price, expected.0);
+        assert_eq!(tax, expected.1);
+    }
+    #[test]
+    fn test_music_cd() {
+        let music_cd = Item::new(
+            1,
+            Money::from_cents(1499),
+            Imported::No,
+            Category::Other("music CD".to_string()),
+        )
+        .unwrap();
+        let (clean_price, tax) = music_cd.get_prices();
+        let expected = (Money::from_cents(1499), Money::from_cents(150));
+        assert_eq!(clean_price, expected.0);
+        assert_eq!(tax, expected.1);
+    }
+    #[test]
+    fn test_imported_box_chocolates() {
+        let box_chocolates = Item::new(
+            1,
+            Money::from_cents(1000),
+            Imported::Yes,
+            Category::Food("".to_string()),
+        )
+        .unwrap();
+        let (clean_price, tax) = box_chocolates.get_prices();
+        let expected = (Money::from_cents(1000), Money::from_cents(50));
+        assert_eq!(clean_price, expected.0);
+        assert_eq!(tax, expected.1);
+    }
+    #[test]
+    fn test_imported_perfume() {
+        let imported_perfume = Item::new(
+            1,
+            Money::from_cents(4750),
+            Imported::Yes,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        let (clean_price, tax) = imported_perfume.get_prices();
+        let expected = (Money::from_cents(4750), Money::from_cents(715));
+        assert_eq!(clean_price, expected.0);
+        assert_eq!(tax, expected.1);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod rounding_tests {
+    use super::RoundingStrategy::*;
+    use super::*;
+    #[test]
+    fn test_round_up_to_next_multiple() {
+        // 5% of 11.25 is 0.5625, which must go up to 0.60 and not down to 0.55.
+        let tax = round_numbers(Money::from_cents(1125), 0.05, Money::from_cents(5), Ceil);
+        assert_eq!(tax, Money::from_cents(60));
+    }
+    #[test]
+    fn test_round_keeps_exact_multiple() {
+        for strategy in [Ceil, HalfUp, Bankers] {
+            let tax = round_numbers(
+                Money::from_cents(1000),
+                0.05,
+                Money::from_cents(5),
+                strategy,
+            );
+            assert_eq!(tax, Money::from_cents(50));
+            let tax = round_numbers(
+                Money::from_cents(1000),
+                0.15,
+                Money::from_cents(5),
+                strategy,
+            );
+            assert_eq!(tax, Money::from_cents(150));
+        }
+    }
+    #[test]
+    fn test_strategies() {
+        let round = |cents, strategy| {
+            round_numbers(
+                Money::from_cents(cents),
+                0.05,
+                Money::from_cents(5),
+                strategy,
+            )
+            .cents()
+        };
+        // 0.5625 is below the half between 0.55 and 0.60.
+        assert_eq!(round(1125, Ceil), 60);
+        assert_eq!(round(1125, HalfUp), 55);
+        assert_eq!(round(1125, Bankers), 55);
+        // 0.025 is exactly halfway between 0.00 and 0.05.
+        assert_eq!(round(50, Ceil), 5);
+        assert_eq!(round(50, HalfUp), 5);
+        assert_eq!(round(50, Bankers), 0);
+        // 0.075 is halfway between 0.05 and 0.10, 0.10 is the even multiple.
+        assert_eq!(round(150, Bankers), 10);
+        // Negative ties mirror the positive ones.
+        assert_eq!(round(-50, Bankers), 0);
+        assert_eq!(round(-150, Bankers), -10);
+        // 0.6375 is above the half between 0.60 and 0.65.
+        assert_eq!(round(1275, HalfUp), 65);
+        assert_eq!(round(1275, Bankers), 65);
+    }
+    #[test]
+    fn test_round_tax() {
+        let round = |cents, increment| {
+            round_tax(Money::from_cents(cents), Money::from_cents(increment)).cents()
+        };
+        assert_eq!(round(56, 5), 60);
+        assert_eq!(round(60, 5), 60);
+        assert_eq!(round(61, 5), 65);
+        assert_eq!(round(0, 5), 0);
+        assert_eq!(round(-56, 5), -55);
+        assert_eq!(round(101, 10), 110);
+        assert_eq!(round(101, 0), 101);
+        assert_eq!(
+            round_tax_default(Money::from_cents(1899)),
+            Money::from_cents(1900)
+        );
+        assert_eq!(
+            round_tax_default(Money::from_cents(5)),
+            Money::from_cents(5)
+        );
+    }
+    #[test]
+    fn test_round_large_amounts() {
+        let amount = Money::from_cents(i64::MAX / 100);
+        assert_eq!(
+            round_tax(amount, Money::from_cents(5)),
+            Money::from_cents(92_233_720_368_547_760)
+        );
+        assert_eq!(
+            round_numbers(amount, 0.10, Money::from_cents(5), Ceil),
+            Money::from_cents(9_223_372_036_854_780)
+        );
+        assert_eq!(
+            round_tax(Money::from_cents(i64::MAX), Money::from_cents(5)),
+            Money::from_cents(i64::MAX)
+        );
+    }
+    #[test]
+    fn test_round_increment_is_the_default() {
+        assert_eq!(TaxPolicy::default().rounding_increment, ROUND_INCREMENT);
+        for cents in [0, 1, 56, 60, 61, 1899, 4199] {
+            let amount = Money::from_cents(cents);
+            assert_eq!(
+                round_tax_default(amount),
+                round_tax(amount, ROUND_INCREMENT)
+            );
+            assert_eq!(
+                round_tax_default(amount).cents() % ROUND_INCREMENT.cents(),
+                0
+            );
+        }
+    }
+    #[test]
+    fn test_policy_strategy() {
+        let chocolates = Item::from_str("1 imported box of chocolates at 11.25").unwrap();
+        let half_up = TaxPolicy {
+            rounding: HalfUp,
+            ..TaxPolicy::default()
+        };
+        assert_eq!(chocolates.get_prices().1, Money::from_cents(60));
+        assert_eq!(
+            chocolates.get_prices_with(&half_up).1,
+            Money::from_cents(55)
+        );
+    }
+    #[test]
+    fn test_imported_chocolates() {
+        let imported_chocolates = Item::new(
+            1,
+            Money::from_cents(1125),
+            Imported::Yes,
+            Category::Food("box of chocolates".to_string()),
+        )
+        .unwrap();
+        let (clean_price, tax) = imported_chocolates.get_prices();
+        assert_eq!(clean_price, Money::from_cents(1125));
+        assert_eq!(tax, Money::from_cents(60));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tax_policy_tests {
+    use super::*;
+    #[test]
+    fn test_default_policy_matches_get_prices() {
+        let imported_perfume = Item::new(
+            1,
+            Money::from_cents(4750),
+            Imported::Yes,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            imported_perfume.get_prices_with(&TaxPolicy::default()),
+            imported_perfume.get_prices()
+        );
+    }
+    #[test]
+    fn test_custom_rates() {
+        let policy = TaxPolicy {
+            basic_rate: 0.20,
+            import_rate: 0.0,
+            ..TaxPolicy::default()
+        };
+        let imported_perfume = Item::new(
+            1,
+            Money::from_cents(4750),
+            Imported::Yes,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        let (clean_price, tax) = imported_perfume.get_prices_with(&policy);
+        assert_eq!(clean_price, Money::from_cents(4750));
+        assert_eq!(tax, Money::from_cents(950));
+    }
+    #[test]
+    fn test_rounding_increment() {
+        let cent = TaxPolicy {
+            rounding_increment: Money::from_cents(1),
+            ..TaxPolicy::default()
+        };
+        let ten_cents = TaxPolicy {
+            rounding_increment: Money::from_cents(10),
+            ..TaxPolicy::default()
+        };
+        // 5% of 11.25 is 0.5625.
+        let chocolates = Item::from_str("1 imported box of chocolates at 11.25").unwrap();
+        assert_eq!(chocolates.get_prices_with(&cent).1, Money::from_cents(57));
+        assert_eq!(chocolates.get_prices().1, Money::from_cents(60));
+        assert_eq!(
+            chocolates.get_prices_with(&ten_cents).1,
+            Money::from_cents(60)
+        );
+        // 10% of 12.49 is 1.249.
+        let music_cd = Item::from_str("1 music CD at 12.49").unwrap();
+        assert_eq!(music_cd.get_prices_with(&cent).1, Money::from_cents(125));
+        assert_eq!(music_cd.get_prices().1, Money::from_cents(125));
+        assert_eq!(
+            music_cd.get_prices_with(&ten_cents).1,
+            Money::from_cents(130)
+        );
+    }
+    #[test]
+    fn test_exempt_below_threshold() {
+        let policy = TaxPolicy {
+            exempt_below: Some(Money::from_cents(1000)),
+            ..TaxPolicy::default()
+        };
+        let under = Item::from_str("1 imported bottle of perfume at 9.99").unwrap();
+        assert_eq!(under.get_prices_with(&policy).1, Money::ZERO);
+        assert_eq!(under.tax_breakdown_with(&policy).import_tax, Money::ZERO);
+        let at = Item::from_str("1 imported bottle of perfume at 10.00").unwrap();
+        assert_eq!(at.get_prices_with(&policy).1, Money::from_cents(150));
+        let over = Item::from_str("1 imported bottle of perfume at 10.01").unwrap();
+        assert_eq!(over.get_prices_with(&policy).1, Money::from_cents(155));
+    }
+    #[test]
+    fn test_threshold_applies_to_unit_price() {
+        let policy = TaxPolicy {
+            exempt_below: Some(Money::from_cents(1000)),
+            ..TaxPolicy::default()
+        };
+        let perfumes = Item::from_str("3 bottles of perfume at 9.99").unwrap();
+        assert_eq!(perfumes.get_prices_with(&policy).1, Money::ZERO);
+    }
+    #[test]
+    fn test_import_exempt_categories() {
+        let policy = TaxPolicy {
+            import_exempt: HashSet::from([CategoryKind::Book]),
+            ..TaxPolicy::default()
+        };
+        let book = Item::from_str("1 imported book at 12.49").unwrap();
+        assert_eq!(book.get_prices().1, Money::from_cents(65));
+        assert_eq!(book.get_prices_with(&policy).1, Money::ZERO);
+        assert_eq!(book.tax_breakdown_with(&policy).import_tax, Money::ZERO);
+        let perfume = Item::from_str("1 imported bottle of perfume at 27.99").unwrap();
+        assert_eq!(perfume.get_prices_with(&policy).1, Money::from_cents(420));
+    }
+    #[test]
+    fn test_no_exempt_categories() {
+        let policy = TaxPolicy {
+            exempt: HashSet::new(),
+            ..TaxPolicy::default()
+        };
+        let book = Item::new(
+            1,
+            Money::from_cents(1249),
+            Imported::No,
+            Category::Book("book".to_string()),
+        )
+        .unwrap();
+        let (_, tax) = book.get_prices_with(&policy);
+        assert_eq!(tax, Money::from_cents(125));
+    }
+    #[test]
+    fn test_category_rate() {
+        let policy = TaxPolicy {
+            category_rates: HashMap::from([(CategoryKind::Other, 0.20)]),
+            ..TaxPolicy::default()
+        };
+        let perfume = Item::from_str("1 bottle of perfume at 18.99").unwrap();
+        assert_eq!(perfume.get_prices_with(&policy).1, Money::from_cents(380));
+        let imported = Item::from_str("1 imported bottle of perfume at 27.99").unwrap();
+        assert_eq!(imported.get_prices_with(&policy).1, Money::from_cents(700));
+        // Other categories keep the basic rate, exempt ones stay exempt.
+        let wine = Item::from_str("1 bottle of wine at 10.00").unwrap();
+        assert_eq!(wine.get_prices_with(&policy).1, Money::from_cents(200));
+        let mut policy = policy;
+        policy.category_rates.insert(CategoryKind::Book, 0.04);
+        let book = Item::from_str("1 book at 12.49").unwrap();
+        assert_eq!(book.get_prices_with(&policy).1, Money::ZERO);
+    }
+    #[test]
+    fn test_taxable_food() {
+        let mut policy = TaxPolicy::default();
+        policy.exempt.remove(&CategoryKind::Food);
+        let chocolates = Item::from_str("1 imported box of chocolates at 11.25").unwrap();
+        assert_eq!(
+            chocolates.get_prices_with(&policy).1,
+            Money::from_cents(170)
+        );
+        let chocolate_bar = Item::from_str("1 chocolate bar at 0.85").unwrap();
+        assert_eq!(
+            chocolate_bar.get_prices_with(&policy).1,
+            Money::from_cents(10)
+        );
+        let book = Item::from_str("1 book at 12.49").unwrap();
+        assert_eq!(book.get_prices_with(&policy).1, Money::ZERO);
+        policy.exempt.insert(CategoryKind::Food);
+        assert_eq!(chocolate_bar.get_prices_with(&policy).1, Money::ZERO);
+    }
+    #[test]
+    fn test_exempt_other() {
+        let mut policy = TaxPolicy::default();
+        policy.exempt.insert(CategoryKind::Other);
+        let perfume = Item::from_str("1 imported bottle of perfume at 27.99").unwrap();
+        assert_eq!(perfume.get_prices_with(&policy).1, Money::from_cents(140));
+        let basket = Basket::<Item>::from_str("1 music CD at 14.99").unwrap();
+        assert_eq!(basket.get_tax(), Money::from_cents(150));
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod policy_config_tests {
+    use super::*;
+    #[test]
+    fn test_from_json_str() {
+        let policy = TaxPolicy::from_json_str(
+            r#"{
+                "basic_rate": 0.10,
+                "import_rate": 0.05,
+                "rounding_increment": 0.05,
+                "exempt": ["Book", "Medical"]
+            }"#,
+        )
+        .unwrap();
+        assert!(!policy.exempt.contains(&CategoryKind::Food));
+        let chocolates = Item::from_str("1 imported box of chocolates at 11.25").unwrap();
+        assert_eq!(
+            chocolates.get_prices_with(&policy).1,
+            Money::from_cents(170)
+        );
+        assert_eq!(
+            TaxPolicy::from_json_str("{}").unwrap(),
+            TaxPolicy::default()
+        );
+    }
+    #[test]
+    fn test_invalid_json() {
+        assert_eq!(
+            TaxPolicy::from_json_str(r#"{"basic_rate": 1.5}"#).unwrap_err(),
+            PolicyError::InvalidRate
+        );
+        assert_eq!(
+            TaxPolicy::from_json_str(r#"{"import_rate": -0.05}"#).unwrap_err(),
+            PolicyError::InvalidRate
+        );
+        assert_eq!(
+            TaxPolicy::from_json_str(r#"{"category_rates": {"Other": 2.0}}"#).unwrap_err(),
+            PolicyError::InvalidRate
+        );
+        assert_eq!(
+            TaxPolicy::from_json_str(r#"{"rounding_increment": 0.0}"#).unwrap_err(),
+            PolicyError::InvalidIncrement
+        );
+        assert_eq!(
+            TaxPolicy::from_json_str(r#"{"rounding_increment": -0.05}"#).unwrap_err(),
+            PolicyError::InvalidIncrement
+        );
+        assert_eq!(
+            TaxPolicy::from_json_str(r#"{"exempt_below": -1.0}"#).unwrap_err(),
+            PolicyError::InvalidThreshold
+        );
+        assert!(TaxPolicy::from_json_str(r#"{"exempt_below": 0.0}"#).is_ok());
+        let policy = TaxPolicy::from_json_str(r#"{"category_rates": {"Other": 0.2}}"#).unwrap();
+        assert_eq!(policy.category_rates[&CategoryKind::Other], 0.2);
+        assert!(matches!(
+            TaxPolicy::from_json_str(r#"{"basic_rat": 0.1}"#).unwrap_err(),
+            PolicyError::Parse(_)
+        ));
+        assert!(matches!(
+            TaxPolicy::from_json_str(r#"{"exempt": ["Toys"]}"#).unwrap_err(),
+            PolicyError::Parse(_)
+        ));
+    }
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml_str() {
+        let policy = TaxPolicy::from_toml_str(
+            r#"
+basic_rate = 0.10
+import_rate = 0.05
+rounding_increment = 0.05
+exempt = ["Book", "Medical"]
+"#,
+        )
+        .unwrap();
+        let chocolate_bar = Item::from_str("1 chocolate bar at 0.85").unwrap();
+        assert_eq!(
+            chocolate_bar.get_prices_with(&policy).1,
+            Money::from_cents(10)
+        );
+        assert_eq!(
+            TaxPolicy::from_toml_str("basic_rate = 2.0").unwrap_err(),
+            PolicyError::InvalidRate
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod compute_tax_tests {
+    use super::*;
+    #[test]
+    fn test_compute_tax() {
+        let policy = TaxPolicy::default();
+        let perfume = Category::Other("bottle of perfume".to_string());
+        let tax = compute_tax(Money::from_cents(4750), Imported::Yes, &perfume, &policy);
+        assert_eq!(tax, Money::from_cents(715));
+        let tax = compute_tax(Money::from_cents(1899), Imported::No, &perfume, &policy);
+        assert_eq!(tax, Money::from_cents(190));
+        let book = Category::Book("book".to_string());
+        let tax = compute_tax(Money::from_cents(1249), Imported::No, &book, &policy);
+        assert_eq!(tax, Money::ZERO);
+        let price = Money::from_cents(i64::MAX / 100);
+        let tax = compute_tax(price, Imported::Yes, &perfume, &policy);
+        assert_eq!(tax, Money::from_cents(13_835_058_055_282_165));
+    }
+    #[test]
+    fn test_item_delegates_to_compute_tax() {
+        let policy = TaxPolicy::default();
+        let item = Item::from_str("1 imported box of chocolates at 11.25").unwrap();
+        let tax = compute_tax(item.clean_price, item.imported, &item.category, &policy);
+        assert_eq!(item.get_prices().1, tax);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tax_matrix_tests {
+    use super::*;
+    #[test]
+    fn test_category_and_import_matrix() {
+        // Tax on 10.00 for every category kind, domestic and imported.
+        let cases = [
+            (CategoryKind::Book, Imported::No, 0),
+            (CategoryKind::Book, Imported::Yes, 50),
+            (CategoryKind::Food, Imported::No, 0),
+            (CategoryKind::Food, Imported::Yes, 50),
+            (CategoryKind::Medical, Imported::No, 0),
+            (CategoryKind::Medical, Imported::Yes, 50),
+            (CategoryKind::Other, Imported::No, 100),
+            (CategoryKind::Other, Imported::Yes, 150),
+            (CategoryKind::Excise, Imported::No, 200),
+            (CategoryKind::Excise, Imported::Yes, 250),
+            (CategoryKind::Service, Imported::No, 0),
+            (CategoryKind::Service, Imported::Yes, 0),
+        ];
+        for (kind, imported, expected) in cases {
+            let item = Item::new(
+                1,
+                Money::from_cents(1000),
+                imported,
+                Category::new(kind, "item"),
+            )
+            .unwrap();
+            assert_eq!(
+                item.get_prices().1,
+                Money::from_cents(expected),
+                "{:?} {:?}",
+                kind,
+                imported
+            );
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tax_breakdown_tests {
+    use super::*;
+    #[test]
+    fn test_audit() {
+        let perfume = Item::from_str("1 imported bottle of perfume at 27.99").unwrap();
+        assert_eq!(perfume.tax_with_audit(), (4.1985, Money::from_cents(420)));
+        let perfumes = Item::from_str("2 bottles of perfume at 18.99").unwrap();
+        assert_eq!(perfumes.tax_with_audit(), (3.798, Money::from_cents(380)));
+        let book = Item::from_str("1 book at 12.49").unwrap();
+        assert_eq!(book.tax_with_audit(), (0.0, Money::ZERO));
+    }
+    #[test]
+    fn test_imported_music_cd() {
+        let music_cd = Item::from_str("1 imported music CD at 14.99").unwrap();
+        let breakdown = music_cd.tax_breakdown();
+        assert_eq!(breakdown.base_tax, Money::from_cents(150));
+        assert_eq!(breakdown.import_tax, Money::from_cents(75));
+        assert_eq!(breakdown.total_tax, Money::from_cents(225));
+        assert_eq!(breakdown.total_tax, music_cd.get_prices().1);
+    }
+    #[test]
+    fn test_imported_exempt_item() {
+        let chocolates = Item::from_str("2 imported box of chocolates at 11.25").unwrap();
+        let breakdown = chocolates.tax_breakdown();
+        assert_eq!(breakdown.base_tax, Money::ZERO);
+        assert_eq!(breakdown.import_tax, Money::from_cents(120));
+        assert_eq!(breakdown.total_tax, Money::from_cents(120));
+    }
+    #[test]
+    fn test_local_item() {
+        let perfume = Item::from_str("1 bottle of perfume at 18.99").unwrap();
+        let breakdown = perfume.tax_breakdown();
+        assert_eq!(breakdown.base_tax, Money::from_cents(190));
+        assert_eq!(breakdown.import_tax, Money::ZERO);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod excise_tests {
+    use super::*;
+    #[test]
+    fn test_parse_excise_items() {
+        let wine = Item::from_str("1 bottle of wine at 10.00").unwrap();
+        assert!(matches!(&wine.category, Category::Excise(x) if x == "bottle of wine"));
+        let cigarettes = Item::from_str("1 imported packet of cigarettes at 8.00").unwrap();
+        assert_eq!(cigarettes.category.kind(), CategoryKind::Excise);
+    }
+    #[test]
+    fn test_excise_taxed_higher_than_other() {
+        let wine = Item::from_str("1 bottle of wine at 10.00").unwrap();
+        let perfume = Item::from_str("1 bottle of perfume at 10.00").unwrap();
+        assert_eq!(wine.get_prices().1, Money::from_cents(200));
+        assert_eq!(perfume.get_prices().1, Money::from_cents(100));
+        let imported_wine = Item::from_str("1 imported bottle of wine at 10.00").unwrap();
+        assert_eq!(imported_wine.get_prices().1, Money::from_cents(250));
+        assert_eq!(
+            imported_wine.to_string(),
+            "1 imported bottle of wine: 12.50"
+        );
+    }
+    #[test]
+    fn test_configurable_excise_rate() {
+        let policy = TaxPolicy {
+            excise_rate: 0.30,
+            ..TaxPolicy::default()
+        };
+        let wine = Item::from_str("1 bottle of wine at 10.00").unwrap();
+        assert_eq!(wine.get_prices_with(&policy).1, Money::from_cents(400));
+    }
+    #[test]
+    fn test_excise_breakdown() {
+        let wine = Item::from_str("1 imported bottle of wine at 10.00").unwrap();
+        let breakdown = wine.tax_breakdown();
+        assert_eq!(breakdown.base_tax, Money::from_cents(100));
+        assert_eq!(breakdown.excise_tax, Money::from_cents(100));
+        assert_eq!(breakdown.import_tax, Money::from_cents(50));
+        assert_eq!(breakdown.total_tax, Money::from_cents(250));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod multiple_item_tests {
+    use super::*;
+    #[test]
+    fn test_purchase_1() {
+        let book = Item::new(
+            1,
+            Money::from_cents(1249),
+            Imported::No,
+            Category::Book("".to_string()),
+        )
+        .unwrap();
+        let book_prices = book.get_prices();
+        let music_cd = Item::new(
+            1,
+            Money::from_cents(1499),
+            Imported::No,
+            Category::Other("CD".to_string()),
+        )
+        .unwrap();
+        let music_cd_prices = music_cd.get_prices();
+        let bar_chocolates = Item::new(
+            1,
+            Money::from_cents(85),
+            Imported::No,
+            Category::Food("".to_string()),
+        )
+        .unwrap();
+        let bar_chocolates_prices = bar_chocolates.get_prices();
+        let clean_price = book_prices.0 + music_cd_prices.0 + bar_chocolates_prices.0;
+        let taxes = book_prices.1 + music_cd_prices.1 + bar_chocolates_prices.1;
+        assert_eq!(clean_price, Money::from_cents(2833));
+        assert_eq!(taxes, Money::from_cents(150));
+    }
+    #[test]
+    fn test_purchase_2() {
+        let chocolates_box = Item::new(
+            1,
+            Money::from_cents(1000),
+            Imported::Yes,
+            Category::Food("".to_string()),
+        )
+        .unwrap();
+        let choc_box_prices = chocolates_box.get_prices();
+        let imported_perfume = Item::new(
+            1,
+            Money::from_cents(4750),
+            Imported::Yes,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        let imported_perf_prices = imported_perfume.get_prices();
+        let clean_price = choc_box_prices.0 + imported_perf_prices.0;
+        let taxes = choc_box_prices.1 + imported_perf_prices.1;
+        assert_eq!(clean_price, Money::from_cents(5750));
+        assert_eq!(taxes, Money::from_cents(765));
+    }
+    #[test]
+    fn test_purchase_3() {
+        let imported_perfume = Item::new(
+            1,
+            Money::from_cents(2799),
+            Imported::Yes,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        let imported_perf_prices = imported_perfume.get_prices();
+        let perfume = Item::new(
+            1,
+            Money::from_cents(1899),
+            Imported::No,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        let perf_prices = perfume.get_prices();
+        let headache_pills = Item::new(
+            1,
+            Money::from_cents(975),
+            Imported::No,
+            Category::Medical("".to_string()),
+        )
+        .unwrap();
+        let pills_prices = headache_pills.get_prices();
+        let imported_chocolates = Item::new(
+            1,
+            Money::from_cents(1125),
+            Imported::Yes,
+            Category::Food("".to_string()),
+        )
+        .unwrap();
+        let imported_choc_prices = imported_chocolates.get_prices();
+
+        let clean_price =
+            imported_perf_prices.0 + perf_prices.0 + pills_prices.0 + imported_choc_prices.0;
+        let taxes =
+            imported_perf_prices.1 + perf_prices.1 + pills_prices.1 + imported_choc_prices.1;
+        assert_eq!(clean_price, Money::from_cents(6798));
+        assert_eq!(taxes, Money::from_cents(670));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod refund_tests {
+    use super::*;
+    #[test]
+    fn test_return_nets_out() {
+        let basket = Basket::<Item>::from_str(
+            "2 imported bottles of perfume at 27.99
+-1 imported bottle of perfume at 27.99
+1 book at 12.49",
+        )
+        .unwrap();
+        assert_eq!(
+            basket.to_string(),
+            "2 imported bottles of perfume: 64.38
+-1 imported bottle of perfume: -32.19
+1 book: 12.49
+Sales Taxes: 4.20
+Total: 44.68"
+        );
+        assert_eq!(basket.total_items(), 2);
+        let refund = basket.iter().nth(1).unwrap();
+        assert_eq!(
+            refund.get_prices(),
+            (Money::from_cents(-2799), Money::from_cents(-420))
+        );
+    }
+    #[test]
+    fn test_full_refund() {
+        let basket = Basket::<Item>::from_str(
+            "1 music CD at 14.99
+-1 music CD at 14.99",
+        )
+        .unwrap();
+        assert_eq!(basket.get_total(), Money::ZERO);
+        assert_eq!(basket.get_tax(), Money::ZERO);
+        assert_eq!(
+            Item::from_str("-1 music CD at -14.99").unwrap_err(),
+            ParseItemError::NegativePrice
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod weight_tests {
+    use super::*;
+    #[test]
+    fn test_half_kilo() {
+        let nails = Item::from_str("0.5 kg of nails at 2.00").unwrap();
+        assert_eq!(nails.quantity, Quantity::Weight(0.5));
+        assert_eq!(
+            nails.get_prices(),
+            (Money::from_cents(100), Money::from_cents(10))
+        );
+        assert_eq!(nails.to_string(), "0.5 kg of nails: 1.10");
+    }
+    #[test]
+    fn test_two_and_a_half_kilos() {
+        let nails = Item::from_str("2.5 kg of imported nails at 1.99").unwrap();
+        // 2.5 * 1.99 = 4.975, rounded half up to 4.98, which pays 15% of taxes
+        // on the whole line: 0.747 rounded up to 0.75.
+        assert_eq!(
+            nails.get_prices(),
+            (Money::from_cents(498), Money::from_cents(75))
+        );
+        assert_eq!(nails.to_string(), "2.5 kg of imported nails: 5.73");
+        let food = Item::builder()
+            .name("kg of rice")
+            .category(CategoryKind::Food)
+            .weight(2.5)
+            .price(Money::from_cents(200))
+            .build()
+            .unwrap();
+        assert_eq!(food.get_prices(), (Money::from_cents(500), Money::ZERO));
+    }
+    #[test]
+    fn test_huge_price_per_unit() {
+        let category = || Category::Other("kg of gold".to_string());
+        let price = Money::from_cents(1_000_000_000_000_000);
+        assert_eq!(
+            Item::weighed(0.001, price, Imported::No, category()).unwrap_err(),
+            ParseItemError::Overflow
+        );
+        let gold = Item::weighed(0.001, Item::MAX_LINE_TOTAL, Imported::No, category())
+            .unwrap()
+            .with_discount(1.0)
+            .unwrap();
+        assert_eq!(gold.get_prices(), (Money::ZERO, Money::ZERO));
+    }
+    #[test]
+    fn test_invalid_weight() {
+        let category = || Category::Food("kg of rice".to_string());
+        for weight in [0.0, 0.0004, -0.5, f64::NAN, f64::INFINITY] {
+            assert_eq!(
+                Item::weighed(weight, Money::from_cents(200), Imported::No, category())
+                    .unwrap_err(),
+                ParseItemError::InvalidQuantity
+            );
+        }
+        assert_eq!(
+            Item::from_str("-0.5 kg of rice at 2.00").unwrap_err(),
+            ParseItemError::InvalidQuantity
+        );
+        assert_eq!(
+            Item::from_str("0.5.1 kg of rice at 2.00").unwrap_err(),
+            ParseItemError::MissingQuantity
+        );
+    }
+    #[test]
+    fn test_weight_in_basket() {
+        let basket = Basket::<Item>::from_str(
+            "0.5 kg of nails at 2.00
+0.5 kg of nails at 2.00
+2 chocolate bars at 0.85",
+        )
+        .unwrap();
+        assert_eq!(basket.total_items(), 4);
+        assert_eq!(basket.to_string_grouped(), basket.to_string());
+        // Merged, 0.2 kg would pay 0.05 of taxes instead of twice 0.05.
+        let basket = Basket::<Item>::from_str(
+            "0.1 kg of nails at 1.00
+0.1 kg of nails at 1.00",
+        )
+        .unwrap();
+        assert_eq!(
+            basket.get_prices(),
+            (Money::from_cents(20), Money::from_cents(10))
+        );
+        assert_eq!(basket.to_string_grouped(), basket.to_string());
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod item_to_string_tests {
+    use super::*;
+    #[test]
+    fn test_imported_marker_is_normalized() {
+        for input in [
+            "1 imported box of chocolates at 10.00",
+            "1 box of imported chocolates at 10.00",
+            "1 box of chocolates imported at 10.00",
+        ] {
+            let chocolates = Item::from_str(input).unwrap();
+            assert_eq!(chocolates.name(), "box of chocolates");
+            assert_eq!(
+                chocolates.to_string(),
+                "1 imported box of chocolates: 10.50"
+            );
+        }
+    }
+    #[test]
+    fn test_book() {
+        let book = Item::new(
+            1,
+            Money::from_cents(1249),
+            Imported::No,
+            Category::Book("book".to_string()),
+        )
+        .unwrap();
+        let book_to_string = "1 book: 12.49".to_string();
+        assert_eq!(book.to_string(), book_to_string);
+    }
+    #[test]
+    fn test_music_cd() {
+        let music_cd = Item::new(
+            1,
+            Money::from_cents(1499),
+            Imported::No,
+            Category::Other("music CD".to_string()),
+        )
+        .unwrap();
+        let music_cd_to_string = "1 music CD: 16.49".to_string();
+        assert_eq!(music_cd.to_string(), music_cd_to_string);
+    }
+    #[test]
+    fn test_display_item() {
+        let music_cd = Item::new(
+            1,
+            Money::from_cents(1499),
+            Imported::No,
+            Category::Other("music CD".to_string()),
+        )
+        .unwrap();
+        assert_eq!(format!("{}", music_cd), "1 music CD: 16.49");
+    }
+    #[test]
+    fn test_two_decimals() {
+        let basket = Basket::<Item>::from_str("1 book at 20").unwrap();
+        assert_eq!(
+            basket.to_string(),
+            "1 book: 20.00
+Sales Taxes: 0.00
+Total: 20.00"
+        );
+        let music_cd = Item::from_str("1 music CD at 13.6").unwrap();
+        assert_eq!(music_cd.to_string(), "1 music CD: 15.00");
+    }
+    #[test]
+    fn test_parse_item_invalid_format() {
+        let input = "1 bottle of perfume 18.99";
+        assert_eq!(
+            Item::from_str(input).unwrap_err(),
+            ParseItemError::MissingAt
+        );
+    }
+    #[test]
+    fn test_parse_item_name_with_at() {
+        let bag = Item::from_str("1 bag at the station at 50.00").unwrap();
+        assert_eq!(bag.name(), "bag at the station");
+        assert_eq!(bag.clean_price, Money::from_cents(5000));
+        assert_eq!(bag.to_string(), "1 bag at the station: 55.00");
+        let watch = Item::from_str("1 platinum watch at 100.00").unwrap();
+        assert_eq!(watch.name(), "platinum watch");
+    }
+    #[test]
+    fn test_parse_item_invalid_price() {
+        let input = "1 bottle of perfume at invalid";
+        assert_eq!(
+            Item::from_str(input).unwrap_err(),
+            ParseItemError::InvalidPrice
+        );
+    }
+    #[test]
+    fn test_parse_item_negative_price() {
+        let input = "1 bottle of perfume at -18.99";
+        assert_eq!(
+            Item::from_str(input).unwrap_err(),
+            ParseItemError::NegativePrice
+        );
+    }
+    #[test]
+    fn test_parse_item_missing_quantity() {
+        let input = "bottle of perfume at 18.99";
+        assert_eq!(
+            Item::from_str(input).unwrap_err(),
+            ParseItemError::MissingQuantity
+        );
+    }
+    #[test]
+    fn test_parse_item_missing_name() {
+        assert_eq!(
+            Item::from_str("1 imported at 10.00").unwrap_err(),
+            ParseItemError::MissingName
+        );
+        assert_eq!(
+            Item::from_str("1  at 10.00").unwrap_err(),
+            ParseItemError::MissingName
+        );
+    }
+    #[test]
+    fn test_parse_item_zero_quantity() {
+        let input = "0 bottle of perfume at 18.99";
+        assert_eq!(
+            Item::from_str(input).unwrap_err(),
+            ParseItemError::InvalidQuantity
+        );
+    }
+    #[test]
+    fn test_parse_item_negative_quantity() {
+        let input = "-2 bottles of perfume at 18.99";
+        let item = Item::from_str(input).unwrap();
+        assert_eq!(item.quantity, Quantity::Count(-2));
+        assert_eq!(item.to_string(), "-2 bottles of perfume: -41.78");
+        let item = Item::from_str("-2147483648 book at 0.00").unwrap();
+        assert_eq!(item.quantity, Quantity::Count(i32::MIN));
+    }
+    #[test]
+    fn test_from_valid() {
+        let book = Item::from_valid(
+            1,
+            Money::from_cents(1249),
+            Imported::No,
+            Category::Book("book".to_string()),
+        );
+        assert_eq!(book.to_string(), "1 book: 12.49");
+    }
+    #[test]
+    #[should_panic(expected = "invalid item: clean_price must be positive")]
+    fn test_from_valid_panics() {
+        Item::from_valid(
+            1,
+            Money::from_cents(-1249),
+            Imported::No,
+            Category::Book("book".to_string()),
+        );
+    }
+    #[test]
+    fn test_new_item_errors() {
+        let category = || Category::Other("bottle of perfume".to_string());
+        let zero = Item::new(0, Money::from_cents(1899), Imported::No, category());
+        assert_eq!(zero.unwrap_err(), ParseItemError::InvalidQuantity);
+        let negative = Item::new(1, Money::from_cents(-1899), Imported::No, category());
+        assert_eq!(negative.unwrap_err(), ParseItemError::NegativePrice);
+    }
+    #[test]
+    fn test_parse_item_error_message() {
+        let error = Item::from_str("1 bottle of perfume 18.99").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid string: missing 'at'");
+    }
+    #[test]
+    fn test_imported_perfumes_quantity() {
+        let perfumes = Item::new(
+            3,
+            Money::from_cents(2799),
+            Imported::Yes,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        assert_eq!(perfumes.to_string(), "3 imported bottles of perfume: 96.57");
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod string_to_item_tests {
+    use super::*;
+    #[test]
+    fn test_parse_item_imported_perfume() {
+        let input = "1 imported bottle of perfume at 27.99";
+        let item = Item::from_str(input).unwrap();
+        assert!(matches!(item.imported, Imported::Yes));
+        assert!(matches!(item.category, Category::Other(_)));
+        assert_eq!(item.clean_price, Money::from_cents(2799));
+    }
+    #[test]
+    fn test_parse_item_regular_perfume() {
+        let input = "1 bottle of perfume at 18.99";
+        let item = Item::from_str(input).unwrap();
+        assert!(matches!(item.imported, Imported::No));
+        assert!(matches!(item.category, Category::Other(_)));
+        assert_eq!(item.clean_price, Money::from_cents(1899));
+    }
+    #[test]
+    fn test_parse_imported_word() {
+        let start = Item::from_str("1 imported bottle of perfume at 27.99").unwrap();
+        assert_eq!(start.imported, Imported::Yes);
+        assert_eq!(start.name(), "bottle of perfume");
+        let middle = Item::from_str("1 box of imported chocolates at 11.25").unwrap();
+        assert_eq!(middle.imported, Imported::Yes);
+        assert_eq!(middle.name(), "box of chocolates");
+        let end = Item::from_str("1 bottle of perfume imported at 27.99").unwrap();
+        assert_eq!(end.imported, Imported::Yes);
+        assert_eq!(end.name(), "bottle of perfume");
+    }
+    #[test]
+    fn test_parse_imported_inside_another_word() {
+        let unimported = Item::from_str("1 unimported widget at 10.00").unwrap();
+        assert_eq!(unimported.imported, Imported::No);
+        assert_eq!(unimported.name(), "unimported widget");
+        let style = Item::from_str("1 imported-style lamp at 10.00").unwrap();
+        assert_eq!(style.imported, Imported::No);
+        assert_eq!(style.get_prices().1, Money::from_cents(100));
+    }
+    #[test]
+    fn test_parse_item_quantity() {
+        let input = "3 imported bottles of perfume at 27.99";
+        let item = Item::from_str(input).unwrap();
+        assert_eq!(item.quantity, Quantity::Count(3));
+        assert!(matches!(item.imported, Imported::Yes));
+        assert!(matches!(&item.category, Category::Other(x) if x == "bottles of perfume"));
+        let (clean_price, tax) = item.get_prices();
+        assert_eq!(clean_price, Money::from_cents(8397));
+        assert_eq!(tax, Money::from_cents(1260));
+    }
+    #[test]
+    fn test_try_from() {
+        let book = Item::try_from("1 book at 12.49").unwrap();
+        assert_eq!(book, Item::from_str("1 book at 12.49").unwrap());
+        assert_eq!(Item::try_from("1 book"), Err(ParseItemError::MissingAt));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod strict_parsing_tests {
+    use super::*;
+    #[test]
+    fn test_unknown_category() {
+        let input = "1 box of imported chocolat at 11.25";
+        let lenient = Item::from_str(input).unwrap();
+        assert_eq!(lenient.category.kind(), CategoryKind::Other);
+        assert_eq!(
+            Item::from_str_strict(input).unwrap_err(),
+            ParseItemError::UnknownCategory
+        );
+    }
+    #[test]
+    fn test_known_category() {
+        let chocolates = Item::from_str_strict("1 box of imported chocolates at 11.25").unwrap();
+        assert_eq!(chocolates.category.kind(), CategoryKind::Food);
+        assert_eq!(
+            Item::from_str_strict("1 book at abc").unwrap_err(),
+            ParseItemError::InvalidPrice
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod category_tests {
+    use super::*;
+    #[test]
+    fn test_kind_and_name() {
+        let kinds = [
+            CategoryKind::Book,
+            CategoryKind::Food,
+            CategoryKind::Medical,
+            CategoryKind::Excise,
+            CategoryKind::Service,
+            CategoryKind::Other,
+        ];
+        for kind in kinds {
+            let category = Category::new(kind, "item");
+            assert_eq!(category.kind(), kind);
+            assert_eq!(category.name(), "item");
+        }
+        assert_eq!(
+            Category::new(CategoryKind::Food, "box of chocolates"),
+            Category::Food("box of chocolates".to_string())
+        );
+    }
+    #[test]
+    fn test_service_is_never_taxed() {
+        let wrap = Item::from_str("1 imported gift wrap at 5.00").unwrap();
+        assert_eq!(wrap.category, Category::Service("gift wrap".to_string()));
+        assert_eq!(wrap.get_prices().1, Money::ZERO);
+        assert_eq!(wrap.tax_breakdown().import_tax, Money::ZERO);
+        let delivery = Item::from_str("1 delivery service at 9.99").unwrap();
+        assert_eq!(delivery.category.kind(), CategoryKind::Service);
+        let policy = TaxPolicy {
+            category_rates: HashMap::from([(CategoryKind::Service, 0.2)]),
+            ..TaxPolicy::default()
+        };
+        assert_eq!(delivery.get_prices_with(&policy).1, Money::ZERO);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod quantity_word_tests {
+    use super::*;
+    #[test]
+    fn test_a_and_an() {
+        let perfume = Item::from_str("a bottle of perfume at 18.99").unwrap();
+        assert_eq!(perfume.quantity, Quantity::Count(1));
+        assert_eq!(perfume.to_string(), "1 bottle of perfume: 20.89");
+        let perfume = Item::from_str("an imported bottle of perfume at 27.99").unwrap();
+        assert_eq!(perfume.quantity, Quantity::Count(1));
+        assert_eq!(perfume.to_string(), "1 imported bottle of perfume: 32.19");
+    }
+    #[test]
+    fn test_number_words() {
+        let books = Item::from_str("three books at 12.49").unwrap();
+        assert_eq!(books.quantity, Quantity::Count(3));
+        assert_eq!(books.to_string(), "3 books: 37.47");
+        let books = Item::from_str("two book at 12.49").unwrap();
+        assert_eq!(books.to_string(), "2 books: 24.98");
+        let bars = Item::from_str("Ten chocolate bars at 0.85").unwrap();
+        assert_eq!(bars.quantity, Quantity::Count(10));
+    }
+    #[test]
+    fn test_unknown_words() {
+        assert_eq!(
+            Item::from_str("eleven books at 12.49").unwrap_err(),
+            ParseItemError::MissingQuantity
+        );
+        assert_eq!(
+            Item::from_str("some books at 12.49").unwrap_err(),
+            ParseItemError::MissingQuantity
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod category_rules_tests {
+    use super::*;
+    #[test]
+    fn test_lint() {
+        let basket = Basket::<Item>::from_str(
+            "1 box of chocolat at 11.25
+1 bottle of perfume at 18.99
+1 box of chocolates at 11.25
+1 packet of headache pill at 9.75
+1 packet of cigarette at 5.00",
+        )
+        .unwrap();
+        let warnings = basket.lint();
+        assert_eq!(
+            warnings,
+            vec![
+                Warning {
+                    index: 0,
+                    name: "box of chocolat".to_string(),
+                    keyword: "chocolate".to_string(),
+                    kind: CategoryKind::Food,
+                },
+                Warning {
+                    index: 3,
+                    name: "packet of headache pill".to_string(),
+                    keyword: "pills".to_string(),
+                    kind: CategoryKind::Medical,
+                },
+                Warning {
+                    index: 4,
+                    name: "packet of cigarette".to_string(),
+                    keyword: "cigarettes".to_string(),
+                    kind: CategoryKind::Excise,
+                },
+            ]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "item 0 (\"box of chocolat\") is taxed as other goods, did you mean \"chocolate\"?"
+        );
+    }
+    #[test]
+    fn test_lint_near_misses() {
+        let basket = Basket::<Item>::from_str(
+            "1 cook at 12.49
+1 wire at 2.00
+1 bee at 5.00
+1 boot at 40.00
+1 wind chime at 15.00
+1 pile of paper at 3.00",
+        )
+        .unwrap();
+        assert_eq!(basket.lint(), vec![]);
+    }
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("chocolat", "chocolate"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "book"), 4);
+        assert_eq!(edit_distance("book", "book"), 0);
+    }
+    #[test]
+    fn test_default_rules() {
+        let rules = CategoryRules::default();
+        assert_eq!(
+            rules.categorize("packet of headache pills"),
+            CategoryKind::Medical
+        );
+        assert_eq!(rules.categorize("box of chocolates"), CategoryKind::Food);
+        assert_eq!(rules.categorize("chocolate bar"), CategoryKind::Food);
+        assert_eq!(rules.categorize("book"), CategoryKind::Book);
+        assert_eq!(rules.categorize("bottle of perfume"), CategoryKind::Other);
+    }
+    #[test]
+    fn test_keywords_match_whole_words() {
+        let rules = CategoryRules::default();
+        assert_eq!(rules.categorize("ball of twine"), CategoryKind::Other);
+        assert_eq!(rules.categorize("bag of swine feed"), CategoryKind::Other);
+        assert_eq!(rules.categorize("bookshelf"), CategoryKind::Other);
+        assert_eq!(rules.categorize("case of wines"), CategoryKind::Excise);
+        assert_eq!(rules.categorize("Books, used"), CategoryKind::Book);
+        let twine = Item::from_str("1 ball of twine at 3.00").unwrap();
+        assert_eq!(
+            twine.get_prices(),
+            (Money::from_cents(300), Money::from_cents(30))
+        );
+    }
+    #[test]
+    fn test_parse_keeps_description_as_name() {
+        let item = Item::from_str("1 box of imported chocolates at 11.25").unwrap();
+        assert!(matches!(&item.category, Category::Food(x) if x == "box of chocolates"));
+    }
+    #[test]
+    fn test_custom_rules() {
+        let mut rules = CategoryRules::default();
+        rules
+            .register("aspirin", CategoryKind::Medical)
+            .register("rice", CategoryKind::Food);
+        let config = ParseConfig::from(rules);
+        let aspirin = Item::from_str_with("1 imported box of aspirin at 5.00", &config).unwrap();
+        assert!(matches!(&aspirin.category, Category::Medical(x) if x == "box of aspirin"));
+        let rice = Item::from_str_with("1 bag of rice at 2.00", &config).unwrap();
+        assert!(matches!(rice.category, Category::Food(_)));
+        let (_, tax) = rice.get_prices();
+        assert_eq!(tax, Money::ZERO);
+    }
+    #[test]
+    fn test_multiple_keywords_first_match_wins() {
+        let rules = CategoryRules::default();
+        assert_eq!(
+            rules.categorize("box of chocolate pills"),
+            CategoryKind::Medical
+        );
+        assert_eq!(
+            rules.categorize("pills in a chocolate box"),
+            CategoryKind::Medical
+        );
+        assert_eq!(
+            rules.categorize("book of chocolate recipes"),
+            CategoryKind::Food
+        );
+        assert_eq!(rules.categorize("book about pills"), CategoryKind::Medical);
+    }
+    #[test]
+    fn test_registration_order_is_priority() {
+        let mut rules = CategoryRules::new();
+        rules
+            .register("perfume", CategoryKind::Other)
+            .register("chocolate", CategoryKind::Food);
+        assert_eq!(rules.categorize("chocolate perfume"), CategoryKind::Other);
+        let mut rules = CategoryRules::new();
+        rules
+            .register("chocolate", CategoryKind::Food)
+            .register("perfume", CategoryKind::Other);
+        assert_eq!(rules.categorize("chocolate perfume"), CategoryKind::Food);
+    }
+    #[test]
+    fn test_empty_rules() {
+        let config = ParseConfig::from(CategoryRules::new());
+        let item = Item::from_str_with("1 book at 12.49", &config).unwrap();
+        assert!(matches!(item.category, Category::Other(_)));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod import_marker_tests {
+    use super::*;
+    #[test]
+    fn test_custom_markers() {
+        let config = ParseConfig {
+            import_markers: vec![
+                "imported".to_string(),
+                "import duty".to_string(),
+                "from abroad".to_string(),
+            ],
+            ..ParseConfig::default()
+        };
+        let perfume =
+            Item::from_str_with("1 import duty bottle of perfume at 27.99", &config).unwrap();
+        assert_eq!(perfume.imported, Imported::Yes);
+        assert_eq!(perfume.name(), "bottle of perfume");
+        assert_eq!(perfume.to_string(), "1 imported bottle of perfume: 32.19");
+        let chocolates =
+            Item::from_str_with("1 box of chocolates from abroad at 11.25", &config).unwrap();
+        assert_eq!(chocolates.imported, Imported::Yes);
+        assert_eq!(chocolates.name(), "box of chocolates");
+        let domestic = Item::from_str_with("1 import bottle of perfume at 18.99", &config).unwrap();
+        assert_eq!(domestic.imported, Imported::No);
+    }
+    #[test]
+    fn test_default_markers() {
+        let perfume = Item::from_str("1 import duty bottle of perfume at 27.99").unwrap();
+        assert_eq!(perfume.imported, Imported::No);
+        let none = ParseConfig {
+            import_markers: Vec::new(),
+            ..ParseConfig::default()
+        };
+        let perfume = Item::from_str_with("1 imported bottle of perfume at 27.99", &none).unwrap();
+        assert_eq!(perfume.imported, Imported::No);
+        assert_eq!(perfume.name(), "imported bottle of perfume");
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod price_precision_tests {
+    use super::*;
+    #[test]
+    fn test_strict_by_default() {
+        assert_eq!(
+            Item::from_str("1 book at 12.499"),
+            Err(ParseItemError::InvalidPrice)
+        );
+    }
+    #[test]
+    fn test_round_prices() {
+        let config = ParseConfig {
+            round_prices: true,
+            ..ParseConfig::default()
+        };
+        let price = |s| Item::from_str_with(s, &config).map(|item| item.clean_price);
+        assert_eq!(price("1 book at 12.499"), Ok(Money::from_cents(1250)));
+        assert_eq!(price("1 book at 12.494"), Ok(Money::from_cents(1249)));
+        assert_eq!(price("1 book at 12.4951"), Ok(Money::from_cents(1250)));
+        assert_eq!(price("1 book at $0.005"), Ok(Money::from_cents(1)));
+        assert_eq!(price("1 book at 12.49"), Ok(Money::from_cents(1249)));
+        assert_eq!(price("1 book at 12.49x"), Err(ParseItemError::InvalidPrice));
+        assert_eq!(price("1 book at 12.4x9"), Err(ParseItemError::InvalidPrice));
+        assert_eq!(price("1 book at 12.4€"), Err(ParseItemError::InvalidPrice));
+        assert_eq!(price("1 book at 12.€5"), Err(ParseItemError::InvalidPrice));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod multiplier_quantity_tests {
+    use super::*;
+    fn config() -> ParseConfig {
+        ParseConfig {
+            multiplier_quantities: true,
+            ..ParseConfig::default()
+        }
+    }
+    #[test]
+    fn test_prefix() {
+        let item = Item::from_str_with("3x book at 12.49", &config()).unwrap();
+        assert_eq!(item.quantity, Quantity::Count(3));
+        assert_eq!(item.name(), "book");
+        assert_eq!(item.to_string(), "3 books: 37.47");
+        let item =
+            Item::from_str_with("2X imported bottle of perfume at 27.99", &config()).unwrap();
+        assert_eq!(item.quantity, Quantity::Count(2));
+        assert_eq!(item.imported, Imported::Yes);
+        assert_eq!(item.to_string(), "2 imported bottles of perfume: 64.38");
+    }
+    #[test]
+    fn test_suffix() {
+        let item = Item::from_str_with("book x3 at 12.49", &config()).unwrap();
+        assert_eq!(item.quantity, Quantity::Count(3));
+        assert_eq!(item.name(), "book");
+        assert_eq!(item.to_string(), "3 books: 37.47");
+        assert!(matches!(item.category, Category::Book(_)));
+    }
+    #[test]
+    fn test_plain_quantity_still_parses() {
+        let item = Item::from_str_with("3 boxes x at 1.00", &config()).unwrap();
+        assert_eq!(item.quantity, Quantity::Count(3));
+        assert_eq!(item.name(), "boxes x");
+        assert_eq!(
+            Item::from_str_with("3x book at 12.49", &ParseConfig::default()),
+            Err(ParseItemError::MissingQuantity)
+        );
+        assert_eq!(
+            Item::from_str_with("0x book at 12.49", &config()),
+            Err(ParseItemError::InvalidQuantity)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod locale_tests {
+    use super::*;
+    #[test]
+    fn test_german() {
+        let config = ParseConfig::german();
+        let chocolate =
+            Item::from_str_with("1 importierte Tafel Schokolade at 2.00", &config).unwrap();
+        assert_eq!(chocolate.category.kind(), CategoryKind::Food);
+        assert_eq!(chocolate.imported, Imported::Yes);
+        assert_eq!(chocolate.name(), "Tafel Schokolade");
+        let pills = Item::from_str_with("1 Packung Kopfschmerztabletten at 9.75", &config).unwrap();
+        assert_eq!(pills.category.kind(), CategoryKind::Medical);
+        let book = Item::from_str_with("1 importiertes Buch at 12.49", &config).unwrap();
+        assert_eq!(book.category.kind(), CategoryKind::Book);
+        assert_eq!(book.get_prices().1, Money::from_cents(65));
+        let perfume = Item::from_str_with("1 Flasche Parfüm at 18.99", &config).unwrap();
+        assert_eq!(perfume.category.kind(), CategoryKind::Other);
+    }
+    #[test]
+    fn test_ignores_case() {
+        let rules = CategoryRules::default();
+        assert_eq!(rules.categorize("Box of Chocolates"), CategoryKind::Food);
+        assert_eq!(
+            CategoryRules::german().categorize("Zartbitterschokolade"),
+            CategoryKind::Food
+        );
+        assert_eq!(ParseConfig::english(), ParseConfig::default());
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_tests {
+    use super::*;
+    #[test]
+    fn test_collect_and_extend() {
+        let input = Basket::<Item>::from_str(
+            "1 book at 12.49\n1 music CD at 14.99\n1 chocolate bar at 0.85",
+        )
+        .unwrap();
+        let mut taxed: Basket<Item> = input
+            .iter()
+            .filter(|item| item.get_prices().1 != Money::ZERO)
+            .cloned()
+            .collect();
+        assert_eq!(taxed.len(), 1);
+        assert_eq!(taxed.get_total(), Money::from_cents(1649));
+        taxed.extend(input);
+        assert_eq!(taxed.len(), 4);
+        assert_eq!(taxed.get_total(), Money::from_cents(1649 + 2983));
+    }
+    #[test]
+    fn test_try_collect_and_extend() {
+        let dollars = || Item::from_str("1 book at $12.49").unwrap();
+        let euros = || Item::from_str("1 book at €12.49").unwrap();
+        assert_eq!(
+            Basket::try_from_iter([dollars(), euros()]).unwrap_err(),
+            BasketError::MixedCurrencies
+        );
+        let mut basket = Basket::try_from_iter([dollars()]).unwrap();
+        assert_eq!(
+            basket.try_extend([dollars(), euros()]).unwrap_err(),
+            BasketError::MixedCurrencies
+        );
+        assert_eq!(basket.len(), 1);
+        basket.try_extend([dollars()]).unwrap();
+        assert_eq!(basket.len(), 2);
+        let mut empty = Basket::default();
+        empty.try_extend([euros(), euros()]).unwrap();
+        assert_eq!(empty.currency(), Some(Currency::Eur));
+    }
+    #[test]
+    #[should_panic(expected = "invalid basket")]
+    fn test_collect_mixed_currencies() {
+        let items = ["1 book at $12.49", "1 book at €12.49"];
+        let _: Basket<Item> = items.iter().map(|line| line.parse().unwrap()).collect();
+    }
+    #[test]
+    fn test_set_price_in_place() {
+        let mut basket = Basket::<Item>::from_str("1 book at 12.49\n1 music CD at 14.99").unwrap();
+        let cd = basket
+            .iter_mut()
+            .find(|item| item.name() == "music CD")
+            .unwrap();
+        cd.set_price(Money::from_cents(1000)).unwrap();
+        assert_eq!(basket.get_total(), Money::from_cents(1249 + 1100));
+        let book = basket.iter_mut().next().unwrap();
+        assert_eq!(
+            book.set_price(Money::from_cents(-1)),
+            Err(ParseItemError::NegativePrice)
+        );
+        assert_eq!(book.clean_price, Money::from_cents(1249));
+    }
+    #[test]
+    fn test_change() {
+        // Total: 29.83
+        let basket = Basket::<Item>::from_str(
+            "1 book at 12.49\n1 music CD at 14.99\n1 chocolate bar at 0.85",
+        )
+        .unwrap();
+        assert_eq!(basket.change(Money::from_cents(2983)), Ok(Money::ZERO));
+        assert_eq!(
+            basket.change(Money::from_cents(5000)),
+            Ok(Money::from_cents(2017))
+        );
+        assert_eq!(
+            basket.change(Money::from_cents(2982)),
+            Err(BasketError::InsufficientPayment)
+        );
+    }
+    #[test]
+    fn test_default() {
+        let mut basket = Basket::default();
+        assert!(basket.is_empty());
+        basket
+            .add(Item::from_str("1 book at 12.49").unwrap())
+            .unwrap();
+        assert_eq!(basket.len(), 1);
+    }
+    #[test]
+    fn test_clear_and_reuse() {
+        let mut basket = Basket::with_capacity(2);
+        assert!(basket.elements.capacity() >= 2);
+        basket
+            .add(Item::from_str("1 book at 12.49").unwrap())
+            .unwrap();
+        basket
+            .add(Item::from_str("1 music CD at 14.99").unwrap())
+            .unwrap();
+        basket.clear();
+        assert!(basket.is_empty());
+        assert_eq!(basket.get_total(), Money::ZERO);
+        assert_eq!(basket.get_tax(), Money::ZERO);
+        basket.reserve(10);
+        assert!(basket.elements.capacity() >= 10);
+        basket
+            .add(Item::from_str("1 chocolate bar at 0.85").unwrap())
+            .unwrap();
+        assert_eq!(basket.get_total(), Money::from_cents(85));
+    }
+    #[test]
+    fn test_taxable_base() {
+        let basket = Basket::<Item>::from_str(
+            "2 book at 12.49\n1 music CD at 14.99\n1 chocolate bar at 0.85\n1 imported box of chocolates at 10.00",
+        )
+        .unwrap();
+        assert_eq!(basket.taxable_base(), Money::from_cents(1499 + 1000));
+        assert_eq!(
+            Basket::<Item>::new(vec![]).unwrap().taxable_base(),
+            Money::ZERO
+        );
+    }
+    #[test]
+    fn test_debug_and_clone() {
+        let basket = Basket::<Item>::from_str("1 book at 12.49").unwrap();
+        let debug = format!("{:?}", basket);
+        assert!(debug.starts_with(
+            "Basket { elements: [Item { quantity: Count(1), clean_price: Money(1249)"
+        ));
+        assert!(debug.contains("category: Book(\"book\")"));
+        assert_eq!(basket.clone(), basket);
+    }
+    #[test]
+    fn test_total() {
+        let imported_perfume = Item::new(
+            1,
+            Money::from_cents(2799),
+            Imported::Yes,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        let perfume = Item::new(
+            1,
+            Money::from_cents(1899),
+            Imported::No,
+            Category::Other("bottle of perfume".to_string()),
+        )
+        .unwrap();
+        let headache_pills = Item::new(
+            1,
+            Money::from_cents(975),
+            Imported::No,
+            Category::Medical("packet of headache pills".to_string()),
+        )
+        .unwrap();
+        let imported_chocolates = Item::new(
+            1,
+            Money::from_cents(1125),
+            Imported::Yes,
+            Category::Food("box of chocolates".to_string()),
+        )
+        .unwrap();
+        let basket = Basket::new(vec![
+            imported_perfume,
+            perfume,
+            headache_pills,
+            imported_chocolates,
+        ])
+        .unwrap();
+        assert_eq!(basket.get_total(), Money::from_cents(7468));
+        assert_eq!(basket.get_tax(), Money::from_cents(670));
+        assert_eq!(basket.get_subtotal(), Money::from_cents(6798));
+        assert_eq!(basket.get_subtotal() + basket.get_tax(), basket.get_total());
+        assert_eq!(
+            basket.to_string(),
+            "1 imported bottle of perfume: 32.19
+1 bottle of perfume: 20.89
+1 packet of headache pills: 9.75
+1 imported box of chocolates: 11.85
+Sales Taxes: 6.70
+Total: 74.68"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_prices_tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct Counted {
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl Tax for Counted {
+        fn get_prices(&self) -> (Money, Money) {
+            self.calls.set(self.calls.get() + 1);
+            (Money::from_cents(100), Money::from_cents(10))
+        }
+    }
+
+    impl fmt::Display for Counted {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("1 counted")
+        }
+    }
+
+    #[test]
+    fn test_many_small_items_sum_exactly() {
+        // Amounts are whole cents, so no compensated summation is needed:
+        // 0.10 added 10 000 times drifts as an f64 but not as Money.
+        let drift: f64 = (0..10_000).map(|_| 0.1).sum();
+        assert_ne!(drift, 1_000.0);
+        let chocolate = Item::from_str("1 chocolate bar at 0.10").unwrap();
+        let basket: Basket<Item> = (0..10_000).map(|_| chocolate.clone()).collect();
+        assert_eq!(basket.get_total(), Money::from_cents(100_000));
+        let cd = Item::from_str("1 music CD at 1.00").unwrap();
+        let taxed: Basket<Item> = (0..10_000).map(|_| cd.clone()).collect();
+        assert_eq!(taxed.get_tax(), Money::from_cents(100_000));
+    }
+
+    fn large_basket(calls: &Rc<Cell<usize>>) -> Basket<Counted> {
+        let elements = (0..10_000)
+            .map(|_| Counted {
+                calls: Rc::clone(calls),
+            })
+            .collect();
+        Basket::new(elements).unwrap()
+    }
+
+    #[test]
+    fn test_total_computes_prices_once_per_element() {
+        let calls = Rc::new(Cell::new(0));
+        let basket = large_basket(&calls);
+        assert_eq!(basket.get_total(), Money::from_cents(1_100_000));
+        assert_eq!(calls.get(), 10_000);
+    }
+    #[test]
+    fn test_display_computes_prices_once_per_element() {
+        let calls = Rc::new(Cell::new(0));
+        let basket = large_basket(&calls);
+        let receipt = basket.to_string();
+        assert!(receipt.ends_with("Sales Taxes: 1000.00\nTotal: 11000.00"));
+        assert_eq!(calls.get(), 10_000);
+    }
+    #[test]
+    fn test_basket_prices() {
+        let basket = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 music CD at 14.99",
+        )
+        .unwrap();
+        assert_eq!(
+            basket.get_prices(),
+            (Money::from_cents(2748), Money::from_cents(150))
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod prices_sum_tests {
+    use super::*;
+    #[test]
+    fn test_sum() {
+        let input = "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        let prices = basket.iter().map(Tax::get_prices).sum::<Prices>();
+        assert_eq!(prices.clean, Money::from_cents(6798));
+        assert_eq!(prices.tax, Money::from_cents(670));
+        assert_eq!(prices.total(), basket.get_total());
+        let split: Prices = basket
+            .iter()
+            .map(|item| Prices::from(item.get_prices()))
+            .sum();
+        assert_eq!(split, prices);
+        assert_eq!(
+            std::iter::empty::<Prices>().sum::<Prices>(),
+            Prices::default()
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_mutation_tests {
+    use super::*;
+    #[test]
+    fn test_add() {
+        let mut basket = Basket::new(vec![]).unwrap();
+        basket
+            .add(Item::from_str("1 book at 12.49").unwrap())
+            .unwrap();
+        assert_eq!(basket.get_total(), Money::from_cents(1249));
+        basket
+            .add(Item::from_str("1 music CD at 14.99").unwrap())
+            .unwrap();
+        assert_eq!(basket.elements.len(), 2);
+        assert_eq!(basket.get_total(), Money::from_cents(2898));
+        assert_eq!(basket.get_tax(), Money::from_cents(150));
+    }
+    #[test]
+    fn test_remove() {
+        let mut basket = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 music CD at 14.99",
+        )
+        .unwrap();
+        let music_cd = basket.remove(1).unwrap();
+        assert_eq!(music_cd.to_string(), "1 music CD: 16.49");
+        assert_eq!(basket.get_total(), Money::from_cents(1249));
+        assert_eq!(basket.get_tax(), Money::ZERO);
+        assert!(basket.remove(1).is_none());
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod currency_tests {
+    use super::*;
+    #[test]
+    fn test_parse_currency() {
+        let dollars = Item::from_str("1 book at $12.49").unwrap();
+        assert_eq!(dollars.clean_price, Money::from_cents(1249));
+        assert_eq!(dollars.currency, Some(Currency::Usd));
+        assert_eq!(dollars.to_string(), "1 book: $12.49");
+        let euros = Item::from_str("1 imported bottle of perfume at €47.50").unwrap();
+        assert_eq!(euros.currency, Some(Currency::Eur));
+        assert_eq!(euros.to_string(), "1 imported bottle of perfume: €54.65");
+        let code = Item::from_str("1 music CD at 14.99 USD").unwrap();
+        assert_eq!(code.clean_price, Money::from_cents(1499));
+        assert_eq!(code.currency, Some(Currency::Usd));
+        let plain = Item::from_str("1 music CD at 14.99").unwrap();
+        assert_eq!(plain.currency, None);
+        assert_eq!(
+            Item::from_str("1 music CD at $").unwrap_err(),
+            ParseItemError::InvalidPrice
+        );
+        assert_eq!(
+            Item::from_str("1 music CD at 14.99 CHF").unwrap_err(),
+            ParseItemError::InvalidPrice
+        );
+    }
+    #[test]
+    fn test_display_with_currency() {
+        let book = Item::from_str("1 book at 12.49")
+            .unwrap()
+            .with_currency(Currency::Usd);
+        assert_eq!(book.to_string(), "1 book: $12.49");
+        let perfume = Item::from_str("1 imported bottle of perfume at 47.50")
+            .unwrap()
+            .with_currency(Currency::Eur);
+        assert_eq!(perfume.to_string(), "1 imported bottle of perfume: €54.65");
+    }
+    #[test]
+    fn test_basket_with_currency() {
+        let basket = Basket::new(vec![
+            Item::from_str("1 book at 12.49")
+                .unwrap()
+                .with_currency(Currency::Gbp),
+            Item::from_str("1 music CD at 14.99")
+                .unwrap()
+                .with_currency(Currency::Gbp),
+        ])
+        .unwrap();
+        assert_eq!(basket.currency(), Some(Currency::Gbp));
+        assert_eq!(
+            basket.to_string(),
+            "1 book: £12.49
+1 music CD: £16.49
+Sales Taxes: £1.50
+Total: £28.98"
+        );
+    }
+    #[test]
+    fn test_new_rejects_mixed_currencies() {
+        let basket = Basket::new(vec![
+            Item::from_str("1 book at 12.49")
+                .unwrap()
+                .with_currency(Currency::Usd),
+            Item::from_str("1 music CD at 14.99")
+                .unwrap()
+                .with_currency(Currency::Eur),
+        ]);
+        assert_eq!(basket.err(), Some(BasketError::MixedCurrencies));
+    }
+    #[test]
+    fn test_add_rejects_mixed_currencies() {
+        let mut basket = Basket::new(vec![Item::from_str("1 book at 12.49")
+            .unwrap()
+            .with_currency(Currency::Usd)])
+        .unwrap();
+        let plain = Item::from_str("1 music CD at 14.99").unwrap();
+        assert_eq!(basket.add(plain), Err(BasketError::MixedCurrencies));
+        assert_eq!(basket.iter().count(), 1);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_extremes_tests {
+    use super::*;
+    #[test]
+    fn test_cheapest_and_most_expensive() {
+        let basket = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 music CD at 11.99
+1 chocolate bar at 0.85
+2 chocolate bars at 0.85",
+        )
+        .unwrap();
+        // The CD costs 13.19 with taxes, more than the book.
+        assert_eq!(basket.most_expensive().unwrap().name(), "music CD");
+        assert_eq!(basket.cheapest().unwrap().name(), "chocolate bar");
+        let tied = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 imported book at 11.89",
+        )
+        .unwrap();
+        assert_eq!(tied.most_expensive().unwrap().imported, Imported::No);
+        assert_eq!(tied.cheapest().unwrap().imported, Imported::No);
+    }
+    #[test]
+    fn test_empty_basket() {
+        let basket = Basket::<Item>::new(Vec::new()).unwrap();
+        assert!(basket.cheapest().is_none());
+        assert!(basket.most_expensive().is_none());
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_filter_tests {
+    use super::*;
+    #[test]
+    fn test_filter_by_category() {
+        let basket = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 music CD at 14.99
+1 chocolate bar at 0.85
+1 imported box of chocolates at 10.00",
+        )
+        .unwrap();
+        let food = basket.filter_by_category(CategoryKind::Food);
+        assert_eq!(
+            food.to_string(),
+            "1 chocolate bar: 0.85
+1 imported box of chocolates: 10.50
+Sales Taxes: 0.50
+Total: 11.35"
+        );
+        assert_eq!(basket.len(), 4);
+        assert!(basket.filter_by_category(CategoryKind::Medical).is_empty());
+    }
+    #[test]
+    fn test_tax_by_category() {
+        let basket = Basket::<Item>::from_str(
+            "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25",
+        )
+        .unwrap();
+        let taxes = basket.tax_by_category();
+        assert_eq!(
+            taxes,
+            HashMap::from([
+                (CategoryKind::Other, Money::from_cents(610)),
+                (CategoryKind::Medical, Money::ZERO),
+                (CategoryKind::Food, Money::from_cents(60)),
+            ])
+        );
+        assert_eq!(taxes.values().copied().sum::<Money>(), basket.get_tax());
+    }
+    #[test]
+    fn test_subtotal_by_category() {
+        let basket = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 music CD at 14.99
+2 chocolate bars at 0.85
+1 imported box of chocolates at 10.00",
+        )
+        .unwrap();
+        let subtotals = basket.subtotal_by_category();
+        assert_eq!(
+            subtotals,
+            HashMap::from([
+                (CategoryKind::Book, Money::from_cents(1249)),
+                (CategoryKind::Other, Money::from_cents(1499)),
+                (CategoryKind::Food, Money::from_cents(1170)),
+            ])
+        );
+        assert_eq!(
+            subtotals.values().copied().sum::<Money>(),
+            basket.get_subtotal()
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_discount_tests {
+    use super::*;
+    #[test]
+    fn test_apply_discount() {
+        let mut basket = Basket::<Item>::from_str(
+            "1 music CD at 20.00
+2 imported bottles of perfume at 40.00
+1 book at 10.00",
+        )
+        .unwrap();
+        assert_eq!(basket.get_subtotal(), Money::from_cents(11000));
+        assert_eq!(basket.get_tax(), Money::from_cents(1400));
+        basket.apply_discount(0.5).unwrap();
+        assert_eq!(basket.get_subtotal(), Money::from_cents(5500));
+        assert_eq!(basket.get_tax(), Money::from_cents(700));
+        assert_eq!(basket.get_total(), Money::from_cents(6200));
+        assert_eq!(
+            basket.to_string(),
+            "1 music CD: 11.00
+2 imported bottles of perfume: 46.00
+1 book: 5.00
+Sales Taxes: 7.00
+Total: 62.00"
+        );
+    }
+    #[test]
+    fn test_invalid_discount() {
+        let mut basket = Basket::<Item>::from_str("1 book at 12.49").unwrap();
+        assert_eq!(
+            basket.apply_discount(1.5).unwrap_err(),
+            ParseItemError::InvalidDiscount
+        );
+        assert_eq!(
+            basket.apply_discount(-0.1).unwrap_err(),
+            ParseItemError::InvalidDiscount
+        );
+        assert_eq!(basket.get_total(), Money::from_cents(1249));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_len_tests {
+    use super::*;
+    #[test]
+    fn test_empty_basket() {
+        let basket = Basket::<Item>::new(vec![]).unwrap();
+        assert!(basket.is_empty());
+        assert_eq!(basket.len(), 0);
+        assert_eq!(basket.total_items(), 0);
+    }
+    #[test]
+    fn test_multi_item_basket() {
+        let basket = Basket::<Item>::from_str(
+            "2 books at 12.49
+1 music CD at 14.99
+3 chocolate bars at 0.85",
+        )
+        .unwrap();
+        assert!(!basket.is_empty());
+        assert_eq!(basket.len(), 3);
+        assert_eq!(basket.total_items(), 6);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod split_by_currency_tests {
+    use super::*;
+    /// A basket mixing currencies, which the parser would reject.
+    fn mixed(lines: &[&str]) -> Basket<Item> {
+        let elements = lines.iter().map(|line| line.parse().unwrap()).collect();
+        Basket { elements }
+    }
+    #[test]
+    fn test_split_by_currency() {
+        let basket = mixed(&[
+            "1 book at $12.49",
+            "1 music CD at €14.99",
+            "1 chocolate bar at $0.85",
+        ]);
+        let baskets = basket.split_by_currency();
+        assert_eq!(baskets.len(), 2);
+        let dollars = &baskets[&Some(Currency::Usd)];
+        assert_eq!(
+            dollars.to_string(),
+            "1 book: $12.49
+1 chocolate bar: $0.85
+Sales Taxes: $0.00
+Total: $13.34"
+        );
+        let euros = &baskets[&Some(Currency::Eur)];
+        assert_eq!(euros.get_total(), Money::from_cents(1649));
+    }
+    #[test]
+    fn test_split_without_currency() {
+        let basket = mixed(&["1 book at 12.49", "1 music CD at £14.99"]);
+        let baskets = basket.split_by_currency();
+        assert_eq!(baskets[&None].get_total(), Money::from_cents(1249));
+        assert_eq!(baskets[&Some(Currency::Gbp)].len(), 1);
+    }
+    #[test]
+    fn test_parse_rejects_mixed_currencies() {
+        let input = "1 book at $12.49
+1 book at 12.49 EUR";
+        let error = BasketParseError {
+            line: 2,
+            text: "1 book at 12.49 EUR".to_string(),
+            source: ParseItemError::MixedCurrencies,
+        };
+        assert_eq!(Basket::<Item>::from_str(input).unwrap_err(), error);
+        assert_eq!(parse_baskets(input).unwrap_err(), error);
+        assert!(parse_baskets("1 book at $12.49\n---\n1 book at 12.49 EUR").is_ok());
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_merge_tests {
+    use super::*;
+    #[test]
+    fn test_merge() {
+        let first = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 music CD at 14.99",
+        )
+        .unwrap();
+        let second = Basket::<Item>::from_str("1 imported bottle of perfume at 47.50").unwrap();
+        let (total, tax) = (
+            first.get_total() + second.get_total(),
+            first.get_tax() + second.get_tax(),
+        );
+        let merged = first.merge(second).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged.get_total(), total);
+        assert_eq!(merged.get_tax(), tax);
+    }
+    #[test]
+    fn test_merge_empty() {
+        let basket = Basket::<Item>::from_str("1 book at 12.49").unwrap();
+        let merged = basket.merge(Basket::new(vec![]).unwrap()).unwrap();
+        assert_eq!(merged.get_total(), Money::from_cents(1249));
+    }
+    #[test]
+    fn test_merge_mixed_currencies() {
+        let dollars = Basket::new(vec![Item::from_str("1 book at 12.49")
+            .unwrap()
+            .with_currency(Currency::Usd)])
+        .unwrap();
+        let euros = Basket::new(vec![Item::from_str("1 book at 12.49")
+            .unwrap()
+            .with_currency(Currency::Eur)])
+        .unwrap();
+        assert_eq!(
+            dollars.merge(euros).err(),
+            Some(BasketError::MixedCurrencies)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod basket_iter_tests {
+    use super::*;
+    #[test]
+    fn test_iter() {
+        let basket = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 music CD at 14.99
+1 chocolate bar at 0.85",
+        )
+        .unwrap();
+        let taxed = basket
+            .iter()
+            .filter(|item| item.get_prices().1 > Money::ZERO)
+            .count();
+        assert_eq!(taxed, 1);
+        assert_eq!(basket.iter().count(), 3);
+    }
+    #[test]
+    fn test_into_iter() {
+        let basket = Basket::<Item>::from_str(
+            "1 book at 12.49
+1 music CD at 14.99",
+        )
+        .unwrap();
+        let lines: Vec<String> = basket.into_iter().map(|item| item.to_string()).collect();
+        assert_eq!(lines, vec!["1 book: 12.49", "1 music CD: 16.49"]);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod string_to_basket_tests {
+    use super::*;
+    #[test]
+    fn test_parse_basket() {
+        let input = "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(basket.elements.len(), 4);
+        assert_eq!(format!("{}", basket), basket.to_string());
+        assert_eq!(basket.get_total(), Money::from_cents(7468));
+        assert_eq!(basket.get_tax(), Money::from_cents(670));
+    }
+    #[test]
+    fn test_parse_basket_trailing_newline() {
+        let input = "1 book at 12.49
+1 music CD at 14.99
+1 chocolate bar at 0.85
+";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(basket.elements.len(), 3);
+    }
+    #[test]
+    fn test_parse_basket_blank_lines() {
+        let input = "
+  1 imported box of chocolates at 10.00  
+
+1 imported bottle of perfume at 47.50
+   ";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(basket.elements.len(), 2);
+        assert_eq!(basket.get_total(), Money::from_cents(6515));
+    }
+    #[test]
+    fn test_parse_basket_comments() {
+        let input = "# Input 2
+1 imported box of chocolates at 10.00
+  # the perfume is taxed twice
+1 imported bottle of perfume at 47.50";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(basket.elements.len(), 2);
+        assert_eq!(basket.get_total(), Money::from_cents(6515));
+        let error = Basket::<Item>::from_str("# header\n1 book 12.49").unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+    #[test]
+    fn test_serialize_item() {
+        let item = Item::from_str("1 imported bottle of perfume at 27.99").unwrap();
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "quantity": 1,
+                "clean_price": 27.99,
+                "imported": true,
+                "category": { "kind": "Other", "name": "bottle of perfume" }
+            })
+        );
+    }
+    #[test]
+    fn test_round_trip_basket() {
+        let input = "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        let json = serde_json::to_string(&basket).unwrap();
+        let parsed: Basket<Item> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.get_total(), basket.get_total());
+        assert_eq!(parsed.get_tax(), basket.get_tax());
+        assert_eq!(parsed.to_string(), basket.to_string());
+    }
+    #[test]
+    fn test_to_json() {
+        let input = "1 book at 12.49
+2 imported bottles of perfume at 47.50";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&basket.to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "items": [
+                    {
+                        "name": "book",
+                        "imported": false,
+                        "quantity": 1,
+                        "unit_clean": 12.49,
+                        "unit_tax": 0.0,
+                        "tax": 0.0,
+                        "tax_rate": 0.0,
+                        "line_total": 12.49
+                    },
+                    {
+                        "name": "bottles of perfume",
+                        "imported": true,
+                        "quantity": 2,
+                        "unit_clean": 47.50,
+                        "unit_tax": 7.15,
+                        "tax": 14.30,
+                        "tax_rate": 1430.0 / 9500.0,
+                        "line_total": 109.30
+                    }
+                ],
+                "sales_taxes": 14.30,
+                "total": 121.79
+            })
+        );
+        assert!(basket
+            .to_string()
+            .ends_with("Sales Taxes: 14.30\nTotal: 121.79"));
+    }
+    #[test]
+    fn test_to_json_tax_rate() {
+        let basket = Basket::<Item>::from_str("1 imported bottle of perfume at 10.00").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&basket.to_json()).unwrap();
+        assert_eq!(json["items"][0]["tax_rate"], 0.15);
+    }
+    #[test]
+    fn test_deserialize_money_precision() {
+        assert!(serde_json::from_str::<Money>("12.49").is_ok());
+        assert!(serde_json::from_str::<Money>("12.499").is_err());
+        assert!(serde_json::from_str::<Money>("1e30").is_err());
+    }
+    #[test]
+    fn test_deserialize_invalid_item() {
+        let item = |quantity: &str, price: &str| {
+            let json = format!(
+                r#"{{"quantity":{},"clean_price":{},"imported":false,"category":{{"kind":"Other","name":"b"}}}}"#,
+                quantity, price
+            );
+            serde_json::from_str::<Item>(&json).map_err(|error| error.to_string())
+        };
+        assert!(item("1", "5.0").is_ok());
+        assert_eq!(
+            item("1", "-5.0").unwrap_err(),
+            "clean_price must be positive"
+        );
+        assert!(item("0", "5.0").is_err());
+        assert!(item("1", "1e30").is_err());
+        assert!(item("1", "20000000000.0").is_err());
+    }
+    #[test]
+    fn test_deserialize_invalid_weight() {
+        let item = |weight: &str| {
+            let json = format!(
+                r#"{{"quantity":{},"clean_price":1.0,"imported":false,"category":{{"kind":"Food","name":"rice"}}}}"#,
+                weight
+            );
+            serde_json::from_str::<Item>(&json)
+        };
+        assert!(item("0.5").is_ok());
+        assert!(item("0.0").is_err());
+        assert!(item("-1.5").is_err());
+        assert!(item("0.0001").is_err());
+    }
+    #[test]
+    fn test_deserialize_invalid_discount() {
+        let item = |discount: &str| {
+            let json = format!(
+                r#"{{"quantity":1,"clean_price":10.0,"imported":false,"category":{{"kind":"Other","name":"b"}},"discount":{}}}"#,
+                discount
+            );
+            serde_json::from_str::<Item>(&json)
+        };
+        assert!(item("0.5").is_ok());
+        assert!(item("5.0").is_err());
+        assert!(item("-0.1").is_err());
+    }
+    #[test]
+    fn test_deserialize_mixed_currencies() {
+        let json = r#"{"elements":[
+            {"quantity":1,"clean_price":1.0,"imported":false,"category":{"kind":"Other","name":"a"},"currency":"Usd"},
+            {"quantity":1,"clean_price":1.0,"imported":false,"category":{"kind":"Other","name":"b"},"currency":"Eur"}
+        ]}"#;
+        let error = serde_json::from_str::<Basket<Item>>(json).unwrap_err();
+        assert_eq!(error.to_string(), "items must share the same currency");
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod grouped_basket_tests {
+    use super::*;
+    #[test]
+    fn test_to_string_grouped() {
+        let input = "1 imported bottle of perfume at 27.99
+1 book at 12.49
+1 imported bottle of perfume at 27.99
+2 imported bottle of perfume at 27.99
+1 bottle of perfume at 27.99";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(
+            basket.to_string_grouped(),
+            "4 imported bottles of perfume: 128.76
+1 book: 12.49
+1 bottle of perfume: 30.79
+Sales Taxes: 19.60
+Total: 172.04"
+        );
+        assert!(basket
+            .to_string()
+            .starts_with("1 imported bottle of perfume: 32.19\n"));
+    }
+    #[test]
+    fn test_grouping_keeps_different_prices_apart() {
+        let input = "1 book at 12.49
+1 book at 10.00";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(basket.to_string_grouped(), basket.to_string());
+    }
+    #[test]
+    fn test_grouping_keeps_returns_apart() {
+        let input = "1 book at 12.49
+-1 book at 12.49
+-2 book at 12.49";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(
+            basket.to_string_grouped(),
+            "1 book: 12.49
+-3 books: -37.47
+Sales Taxes: 0.00
+Total: -24.98"
+        );
+    }
+    #[test]
+    fn test_grouping_overflow() {
+        let input = "2000000000 book at 0.00
+2000000000 book at 0.00";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(basket.to_string_grouped(), basket.to_string());
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod sorted_basket_tests {
+    use super::*;
+    fn basket() -> Basket<Item> {
+        Basket::<Item>::from_str(
+            "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25",
+        )
+        .unwrap()
+    }
+    #[test]
+    fn test_price_ascending() {
+        assert_eq!(
+            basket().to_string_sorted(SortOrder::PriceAscending),
+            "1 packet of headache pills: 9.75
+1 imported box of chocolates: 11.85
+1 bottle of perfume: 20.89
+1 imported bottle of perfume: 32.19
+Sales Taxes: 6.70
+Total: 74.68"
+        );
+    }
+    #[test]
+    fn test_price_descending() {
+        assert_eq!(
+            basket().to_string_sorted(SortOrder::PriceDescending),
+            "1 imported bottle of perfume: 32.19
+1 bottle of perfume: 20.89
+1 imported box of chocolates: 11.85
+1 packet of headache pills: 9.75
+Sales Taxes: 6.70
+Total: 74.68"
+        );
+    }
+    #[test]
+    fn test_name() {
+        assert_eq!(
+            basket().to_string_sorted(SortOrder::Name),
+            "1 imported bottle of perfume: 32.19
+1 bottle of perfume: 20.89
+1 imported box of chocolates: 11.85
+1 packet of headache pills: 9.75
+Sales Taxes: 6.70
+Total: 74.68"
+        );
+    }
+    #[test]
+    fn test_by_category() {
+        assert_eq!(
+            basket().to_string_by_category(),
+            "1 imported box of chocolates: 11.85
+1 packet of headache pills: 9.75
+1 imported bottle of perfume: 32.19
+1 bottle of perfume: 20.89
+Sales Taxes: 6.70
+Total: 74.68"
+        );
+        assert!(CategoryKind::Book < CategoryKind::Food);
+        assert!(CategoryKind::Service < CategoryKind::Other);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod receipt_tests {
+    use super::*;
+    #[test]
+    fn test_labels() {
+        let input = "1 book at 12.49
+2 imported bottles of perfume at 47.50";
+        let receipt = Basket::<Item>::from_str(input).unwrap().receipt();
+        let french = ReceiptLabels {
+            items: Some("Articles :".to_string()),
+            sales_taxes: "Taxes:".to_string(),
+            total: "Total:".to_string(),
+            tax_rate: None,
+        };
+        assert_eq!(
+            receipt.to_string_with_labels(&french),
+            "1 book: 12.49
+2 imported bottles of perfume: 109.30
+Articles : 3
+Taxes: 14.30
+Total: 121.79"
+        );
+        assert_eq!(
+            receipt.to_string_with_labels(&ReceiptLabels::default()),
+            receipt.to_string()
+        );
+    }
+    #[test]
+    fn test_receipt() {
+        let input = "1 book at 12.49
+2 imported bottles of perfume at 47.50";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        let receipt = basket.receipt();
+        assert_eq!(
+            receipt.lines,
+            vec![
+                ReceiptLine {
+                    name: "book".to_string(),
+                    imported: Imported::No,
+                    quantity: Quantity::Count(1),
+                    unit_clean: Money::from_cents(1249),
+                    unit_tax: Money::ZERO,
+                    tax: Money::ZERO,
+                    tax_rate: 0.0,
+                    line_total: Money::from_cents(1249),
+                },
+                ReceiptLine {
+                    name: "bottles of perfume".to_string(),
+                    imported: Imported::Yes,
+                    quantity: Quantity::Count(2),
+                    unit_clean: Money::from_cents(4750),
+                    unit_tax: Money::from_cents(715),
+                    tax: Money::from_cents(1430),
+                    tax_rate: 1430.0 / 9500.0,
+                    line_total: Money::from_cents(10930),
+                },
+            ]
+        );
+        assert_eq!(receipt.currency, None);
+        assert_eq!(receipt.sales_taxes, Money::from_cents(1430));
+        assert_eq!(receipt.total, Money::from_cents(12179));
+        assert_eq!(receipt.to_string(), basket.to_string());
+    }
+    #[test]
+    fn test_lines_of_purchase_1() {
+        let input = "1 book at 12.49
+1 music CD at 14.99
+1 chocolate bar at 0.85";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        let lines: Vec<_> = basket
+            .lines()
+            .into_iter()
+            .map(|line| {
+                (
+                    line.name,
+                    line.quantity,
+                    line.unit_clean.cents(),
+                    line.unit_tax.cents(),
+                    line.line_total.cents(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("book".to_string(), Quantity::Count(1), 1249, 0, 1249),
+                ("music CD".to_string(), Quantity::Count(1), 1499, 150, 1649),
+                ("chocolate bar".to_string(), Quantity::Count(1), 85, 0, 85),
+            ]
+        );
     }
-    fn get_tax(&self) -> f64 {
-        self.elements
-            .iter()
-            .fold(0.0, |acc, x| acc + x.get_prices().1)
+    #[test]
+    fn test_receipt_currency() {
+        let item = Item::from_str("1 music CD at 14.99")
+            .unwrap()
+            .with_currency(Currency::Gbp);
+        let basket = Basket::new(vec![item]).unwrap();
+        let receipt = basket.receipt();
+        assert_eq!(receipt.currency, Some(Currency::Gbp));
+        assert_eq!(receipt.to_string(), basket.to_string());
     }
 }
 
-impl<T> ToString for Basket<T>
-where
-    T: Tax + ToString,
-{
-    fn to_string(&self) -> String {
-        let mut string_element: Vec<String> = self.elements.iter().map(|s| s.to_string()).collect();
-        string_element.push(format!(
-            "Sales Taxes: {:.2}",
-            (self.get_tax() * 100.0).round() / 100.0
-        ));
-        string_element.push(format!(
-            "Total: {:.2}",
-            (self.get_total() * 100.0).round() / 100.0
-        ));
-        string_element.join("\n")
+#[cfg(all(test, feature = "std"))]
+mod summary_tests {
+    use super::*;
+    #[test]
+    fn test_effective_tax_rate() {
+        let basket = Basket::<Item>::from_str("1 bottle of perfume at 10.00").unwrap();
+        assert!(basket
+            .to_string_verbose()
+            .ends_with("Total: 11.00\nEffective Tax Rate: 10.00%"));
+        let exempt = Basket::<Item>::from_str("1 book at 12.49\n1 chocolate bar at 0.85").unwrap();
+        assert!(exempt
+            .to_string_verbose()
+            .ends_with("\nEffective Tax Rate: 0.00%"));
+        let empty = Basket::<Item>::default();
+        assert!(empty
+            .to_string_verbose()
+            .ends_with("\nEffective Tax Rate: 0.00%"));
     }
-}
-
-impl FromStr for Basket<Item> {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let items: Result<Vec<Item>, _> = s.lines().map(|line| Item::from_str(line)).collect();
-        items.map(Basket::new)
+    #[test]
+    fn test_summary_line() {
+        let input = "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(basket.summary_line(), "4 items, tax 6.70, total 74.68");
+        let book = Basket::<Item>::from_str("1 book at $12.49").unwrap();
+        assert_eq!(book.summary_line(), "1 item, tax $0.00, total $12.49");
+        let returned = Basket::<Item>::from_str("-1 book at 12.49").unwrap();
+        assert_eq!(returned.summary_line(), "-1 item, tax 0.00, total -12.49");
+    }
+    #[test]
+    fn test_to_string_with_summary() {
+        let input = "1 book at 12.49
+2 music CDs at 14.99
+3 chocolate bars at 0.85";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(
+            basket.to_string_with_summary(),
+            "1 book: 12.49
+2 music CDs: 32.98
+3 chocolate bars: 2.55
+Items: 6
+Sales Taxes: 3.00
+Total: 48.02"
+        );
+        assert!(!basket.to_string().contains("Items:"));
+    }
+    #[test]
+    fn test_to_string_verbose() {
+        let input = "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(
+            basket.to_string_verbose(),
+            "1 imported bottle of perfume: 32.19 (tax 4.20)
+1 bottle of perfume: 20.89 (tax 1.90)
+1 packet of headache pills: 9.75 (tax 0.00)
+1 imported box of chocolates: 11.85 (tax 0.60)
+Sales Taxes: 6.70
+Total: 74.68
+Effective Tax Rate: 9.86%"
+        );
+        let receipt = basket.receipt();
+        let line_taxes: Money = receipt.lines.iter().map(|line| line.tax).sum();
+        assert_eq!(line_taxes, receipt.sales_taxes);
     }
 }
 
-#[cfg(test)]
-mod tests {
+#[cfg(all(test, feature = "std"))]
+mod calculator_tests {
     use super::*;
-    use approx::assert_relative_eq;
-    #[test]
-    fn test_book() {
-        let book = Item::new(12.49, Imported::No, Category::Book("book".to_string())).unwrap();
-        let (clean_price, tax) = book.get_prices();
-        let expected = (12.49, 0.0);
-        assert_relative_eq!(clean_price, expected.0, epsilon = f64::EPSILON);
-        assert_relative_eq!(tax, expected.1, epsilon = f64::EPSILON);
-    }
     #[test]
-    fn test_music_cd() {
-        let music_cd =
-            Item::new(14.99, Imported::No, Category::Other("music CD".to_string())).unwrap();
-        let (clean_price, tax) = music_cd.get_prices();
-        let expected = (14.99, 1.5);
-        assert_relative_eq!(clean_price, expected.0, epsilon = f64::EPSILON);
-        assert_relative_eq!(tax, expected.1, epsilon = f64::EPSILON);
+    fn test_basket_with_policy() {
+        let basket =
+            Basket::<Item>::from_str("1 bottle of perfume at 18.99\n1 book at 12.49").unwrap();
+        let higher = TaxPolicy {
+            basic_rate: 0.2,
+            ..TaxPolicy::default()
+        };
+        assert_eq!(basket.tax_with(&TaxPolicy::default()), basket.get_tax());
+        assert_eq!(basket.total_with(&TaxPolicy::default()), basket.get_total());
+        assert_eq!(basket.tax_with(&higher), Money::from_cents(380));
+        assert_eq!(
+            basket.total_with(&higher),
+            Money::from_cents(1899 + 1249 + 380)
+        );
+        assert_eq!(basket.get_tax(), Money::from_cents(190));
     }
     #[test]
-    fn test_imported_box_chocolates() {
-        let box_chocolates =
-            Item::new(10.00, Imported::Yes, Category::Food("".to_string())).unwrap();
-        let (clean_price, tax) = box_chocolates.get_prices();
-        let expected = (10.0, 0.50);
-        assert_relative_eq!(clean_price, expected.0, epsilon = f64::EPSILON);
-        assert_relative_eq!(tax, expected.1, epsilon = f64::EPSILON);
+    fn test_purchase_3() {
+        let input = "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        let calculator = Calculator::default();
+        assert_eq!(
+            calculator.prices(&basket),
+            (Money::from_cents(6798), Money::from_cents(670))
+        );
+        assert_eq!(calculator.total(&basket), Money::from_cents(7468));
+        assert_eq!(
+            calculator.receipt(&basket).to_string(),
+            "1 imported bottle of perfume: 32.19
+1 bottle of perfume: 20.89
+1 packet of headache pills: 9.75
+1 imported box of chocolates: 11.85
+Sales Taxes: 6.70
+Total: 74.68"
+        );
+        let chocolates = basket.iter().nth(3).unwrap();
+        assert_eq!(
+            calculator.price(chocolates),
+            (Money::from_cents(1125), Money::from_cents(60))
+        );
     }
     #[test]
-    fn test_imported_perfume() {
-        let imported_perfume = Item::new(
-            47.50,
-            Imported::Yes,
-            Category::Other("bottle of perfume".to_string()),
+    fn test_round_scope() {
+        let per_item = TaxPolicy {
+            rounding_increment: Money::from_cents(1),
+            ..TaxPolicy::default()
+        };
+        let per_basket = TaxPolicy {
+            round_per: RoundScope::Basket,
+            ..per_item.clone()
+        };
+        // 10% of 1.01 is 0.101: 0.11 per item, but 0.202 rounds to 0.21.
+        let basket = Basket::<Item>::from_str(
+            "1 music CD at 1.01
+1 pen at 1.01",
         )
         .unwrap();
-        let (clean_price, tax) = imported_perfume.get_prices();
-        let expected = (47.50, 7.15);
-        assert_relative_eq!(clean_price, expected.0, epsilon = f64::EPSILON);
-        assert_relative_eq!(tax, expected.1, epsilon = f64::EPSILON);
+        assert_eq!(
+            Calculator::new(per_item).prices(&basket),
+            (Money::from_cents(202), Money::from_cents(22))
+        );
+        let calculator = Calculator::new(per_basket);
+        assert_eq!(
+            calculator.prices(&basket),
+            (Money::from_cents(202), Money::from_cents(21))
+        );
+        assert_eq!(calculator.total(&basket), Money::from_cents(223));
+        let receipt = calculator.receipt(&basket);
+        assert_eq!(receipt.sales_taxes, Money::from_cents(21));
+        assert_eq!(receipt.lines[0].tax, Money::from_cents(11));
+        // The basket itself rounds per item, with the default policy.
+        assert_eq!(basket.get_tax(), Money::from_cents(30));
+    }
+    #[test]
+    fn test_custom_policy() {
+        let calculator = Calculator::new(TaxPolicy {
+            basic_rate: 0.20,
+            ..TaxPolicy::default()
+        });
+        let basket = Basket::<Item>::from_str("1 music CD at 10.00").unwrap();
+        assert_eq!(
+            calculator.receipt(&basket).to_string(),
+            "1 music CD: 12.00
+Sales Taxes: 2.00
+Total: 12.00"
+        );
+        assert_eq!(calculator.policy().basic_rate, 0.20);
     }
 }
 
-#[cfg(test)]
-mod multiple_item_tests {
+#[cfg(all(test, feature = "std"))]
+mod html_tests {
     use super::*;
-    use approx::assert_relative_eq;
     #[test]
-    fn test_purchase_1() {
-        let book = Item::new(12.49, Imported::No, Category::Book("".to_string())).unwrap();
-        let book_prices = book.get_prices();
-        let music_cd = Item::new(14.99, Imported::No, Category::Other("CD".to_string())).unwrap();
-        let music_cd_prices = music_cd.get_prices();
-        let bar_chocolates = Item::new(0.85, Imported::No, Category::Food("".to_string())).unwrap();
-        let bar_chocolates_prices = bar_chocolates.get_prices();
-        let clean_price = book_prices.0 + music_cd_prices.0 + bar_chocolates_prices.0;
-        let taxes = book_prices.1 + music_cd_prices.1 + bar_chocolates_prices.1;
-        assert_relative_eq!(clean_price, 28.33, epsilon = f64::EPSILON);
-        assert_relative_eq!(taxes, 1.50, epsilon = f64::EPSILON);
+    fn test_to_html() {
+        let input = "1 book at 12.49
+1 imported bottle of perfume at 47.50";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(
+            basket.to_html(),
+            "<table>
+<tbody>
+<tr><td>1</td><td>book</td><td>12.49</td></tr>
+<tr><td>1</td><td>imported bottle of perfume</td><td>54.65</td></tr>
+</tbody>
+<tfoot>
+<tr><td colspan=\"2\">Sales Taxes</td><td>7.15</td></tr>
+<tr><td colspan=\"2\">Total</td><td>67.14</td></tr>
+</tfoot>
+</table>"
+        );
     }
     #[test]
-    fn test_purchase_2() {
-        let chocolates_box =
-            Item::new(10.00, Imported::Yes, Category::Food("".to_string())).unwrap();
-        let choc_box_prices = chocolates_box.get_prices();
-        let imported_perfume = Item::new(
-            47.50,
-            Imported::Yes,
-            Category::Other("bottle of perfume".to_string()),
-        )
-        .unwrap();
-        let imported_perf_prices = imported_perfume.get_prices();
-        let clean_price = choc_box_prices.0 + imported_perf_prices.0;
-        let taxes = choc_box_prices.1 + imported_perf_prices.1;
-        assert_relative_eq!(clean_price, 57.50, epsilon = f64::EPSILON);
-        assert_relative_eq!(taxes, 7.65, epsilon = f64::EPSILON);
+    fn test_escaped_name() {
+        let basket = Basket::<Item>::from_str("1 <b>bold</b> & \"quoted\" CD at 14.99").unwrap();
+        let html = basket.to_html();
+        assert!(html.contains(
+            "<td>&lt;b&gt;bold&lt;/b&gt; &amp; &quot;quoted&quot; CD</td><td>16.49</td>"
+        ));
+        assert!(!html.contains("<b>"));
     }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod bundle_tests {
+    use super::*;
     #[test]
-    fn test_purchase_3() {
-        let imported_perfume = Item::new(
-            27.99,
-            Imported::Yes,
-            Category::Other("bottle of perfume".to_string()),
-        )
-        .unwrap();
-        let imported_perf_prices = imported_perfume.get_prices();
-        let perfume = Item::new(
-            18.99,
-            Imported::No,
-            Category::Other("bottle of perfume".to_string()),
+    fn test_bundle() {
+        let meal = Basket::<Item>::from_str(
+            "1 sandwich at 4.50
+1 chocolate bar at 0.85
+1 imported bottle of beer at 2.00",
         )
         .unwrap();
-        let perf_prices = perfume.get_prices();
-        let headache_pills =
-            Item::new(9.75, Imported::No, Category::Medical("".to_string())).unwrap();
-        let pills_prices = headache_pills.get_prices();
-        let imported_chocolates =
-            Item::new(11.25, Imported::Yes, Category::Food("".to_string())).unwrap();
-        let imported_choc_prices = imported_chocolates.get_prices();
+        let deal = BundleItem::new("meal deal", meal.clone());
+        assert_eq!(deal.get_prices(), meal.get_prices());
+        assert_eq!(deal.to_string(), "1 meal deal: 8.30");
+        let basket = Basket::new(vec![deal, BundleItem::new("snack", meal)]).unwrap();
+        assert_eq!(
+            basket.to_string(),
+            "1 meal deal: 8.30
+1 snack: 8.30
+Sales Taxes: 1.90
+Total: 16.60"
+        );
+    }
+}
 
-        let clean_price =
-            imported_perf_prices.0 + perf_prices.0 + pills_prices.0 + imported_choc_prices.0;
-        let taxes =
-            imported_perf_prices.1 + perf_prices.1 + pills_prices.1 + imported_choc_prices.1;
-        assert_relative_eq!(clean_price, 67.98, epsilon = f64::EPSILON);
-        assert_relative_eq!(taxes, 6.65, epsilon = f64::EPSILON);
+#[cfg(all(test, feature = "std"))]
+mod markdown_tests {
+    use super::*;
+    #[test]
+    fn test_to_markdown() {
+        let input = "1 book at 12.49
+2 imported bottles of perfume at 47.50";
+        let basket = Basket::<Item>::from_str(input).unwrap();
+        assert_eq!(
+            basket.to_markdown(),
+            "| Item | Qty | Price | Tax |
+|:---|---:|---:|---:|
+| book | 1 | 12.49 | 0.00 |
+| imported bottles of perfume | 2 | 109.30 | 14.30 |
+| **Total** | 3 | 121.79 | 14.30 |"
+        );
+    }
+    #[test]
+    fn test_escaped_pipe() {
+        let basket = Basket::<Item>::from_str("1 A|B music CD at 14.99").unwrap();
+        assert!(basket
+            .to_markdown()
+            .contains("\n| A\\|B music CD | 1 | 16.49 | 1.50 |\n"));
     }
 }
 
-#[cfg(test)]
-mod item_to_string_tests {
+#[cfg(all(test, feature = "std"))]
+mod parse_baskets_tests {
     use super::*;
     #[test]
-    fn test_book() {
-        let book = Item::new(12.49, Imported::No, Category::Book("book".to_string())).unwrap();
-        let book_to_string = "1 book: 12.49".to_string();
-        assert_eq!(book.to_string(), book_to_string);
+    fn test_two_baskets() {
+        let input = "1 book at 12.49
+1 music CD at 14.99
+1 chocolate bar at 0.85
+
+1 imported box of chocolates at 10.00
+---
+1 imported bottle of perfume at 47.50
+";
+        let baskets = parse_baskets(input).unwrap();
+        assert_eq!(baskets.len(), 3);
+        assert_eq!(baskets[0].get_total(), Money::from_cents(2983));
+        assert_eq!(baskets[1].get_total(), Money::from_cents(1050));
+        assert_eq!(baskets[2].get_total(), Money::from_cents(5465));
     }
     #[test]
-    fn test_music_cd() {
-        let music_cd =
-            Item::new(14.99, Imported::No, Category::Other("music CD".to_string())).unwrap();
-        let music_cd_to_string = "1 music CD: 16.49".to_string();
-        assert_eq!(music_cd.to_string(), music_cd_to_string);
+    fn test_separators_only() {
+        assert_eq!(parse_baskets("\n---\n\n# nothing\n"), Ok(Vec::new()));
     }
     #[test]
-    fn test_parse_item_invalid_format() {
-        let input = "1 bottle of perfume 18.99";
-        assert!(Item::from_str(input).is_err());
+    fn test_error_line() {
+        let error = parse_baskets("1 book at 12.49\n---\n1 music CD 14.99").unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.source, ParseItemError::MissingAt);
     }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod parse_items_tests {
+    use super::*;
     #[test]
-    fn test_parse_item_invalid_price() {
-        let input = "1 bottle of perfume at invalid";
-        assert!(Item::from_str(input).is_err());
+    fn test_sum_tax_lazily() {
+        let input = "# Input 3
+1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25
+";
+        let tax = parse_items(input.as_bytes())
+            .map(|item| item.unwrap().get_prices().1)
+            .sum::<Money>();
+        assert_eq!(tax, Money::from_cents(670));
     }
     #[test]
-    fn test_parse_item_negative_price() {
-        let input = "1 bottle of perfume at -18.99";
-        assert!(Item::from_str(input).is_err());
+    fn test_errors() {
+        let mut items =
+            parse_items("1 book at 12.49\n1 music CD\n1 chocolate bar at 0.85".as_bytes());
+        assert!(items.next().unwrap().is_ok());
+        match items.next().unwrap() {
+            Err(ReadError::Item(error)) => {
+                assert_eq!(error.line, 2);
+                assert_eq!(error.source, ParseItemError::MissingAt);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert!(items.next().unwrap().is_ok());
+        assert!(items.next().is_none());
+        let invalid_utf8: &[u8] = b"1 book at 12.49\n\xff at 1.00\n";
+        let results: Vec<_> = parse_items(invalid_utf8).collect();
+        assert!(matches!(results[1], Err(ReadError::Read(_))));
     }
 }
 
-#[cfg(test)]
-mod string_to_item_tests {
+#[cfg(all(test, feature = "std"))]
+mod basket_parse_error_tests {
     use super::*;
-    use approx::assert_relative_eq;
     #[test]
-    fn test_parse_item_imported_perfume() {
-        let input = "1 imported bottle of perfume at 27.99";
-        let item = Item::from_str(input).unwrap();
-        assert!(matches!(item.imported, Imported::Yes));
-        assert!(matches!(item.category, Category::Other(_)));
-        assert_relative_eq!(item.clean_price, 27.99, epsilon = f64::EPSILON);
+    fn test_error_line_number() {
+        let input = "1 book at 12.49
+
+1 music CD at invalid
+1 chocolate bar at 0.85";
+        let error = Basket::<Item>::from_str(input).err().unwrap();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.text, "1 music CD at invalid");
+        assert_eq!(error.source, ParseItemError::InvalidPrice);
+        assert_eq!(
+            error.to_string(),
+            "line 3 (\"1 music CD at invalid\"): Price is not valid"
+        );
     }
     #[test]
-    fn test_parse_item_regular_perfume() {
-        let input = "1 bottle of perfume at 18.99";
-        let item = Item::from_str(input).unwrap();
-        assert!(matches!(item.imported, Imported::No));
-        assert!(matches!(item.category, Category::Other(_)));
-        assert_relative_eq!(item.clean_price, 18.99, epsilon = f64::EPSILON);
+    fn test_kata_purchases_totals() {
+        let purchases = [
+            (
+                "1 book at 12.49
+1 music CD at 14.99
+1 chocolate bar at 0.85",
+                150,
+                2983,
+            ),
+            (
+                "1 imported box of chocolates at 10.00
+1 imported bottle of perfume at 47.50",
+                765,
+                6515,
+            ),
+            (
+                "1 imported bottle of perfume at 27.99
+1 bottle of perfume at 18.99
+1 packet of headache pills at 9.75
+1 box of imported chocolates at 11.25",
+                670,
+                7468,
+            ),
+        ];
+        for (input, tax, total) in purchases {
+            let basket = Basket::<Item>::from_str(input).unwrap();
+            assert_eq!(basket.get_tax(), Money::from_cents(tax));
+            assert_eq!(basket.get_total(), Money::from_cents(total));
+        }
     }
 }
 
-#[cfg(test)]
-mod basket_tests {
+#[cfg(all(test, feature = "csv"))]
+mod csv_tests {
     use super::*;
-    use approx::assert_relative_eq;
     #[test]
-    fn test_total() {
-        let imported_perfume = Item::new(
-            27.99,
-            Imported::Yes,
-            Category::Other("bottle of perfume".to_string()),
-        )
-        .unwrap();
-        let perfume = Item::new(
-            18.99,
-            Imported::No,
-            Category::Other("bottle of perfume".to_string()),
-        )
-        .unwrap();
-        let headache_pills = Item::new(
-            9.75,
-            Imported::No,
-            Category::Medical("packet of headache pills".to_string()),
-        )
-        .unwrap();
-        let imported_chocolates = Item::new(
-            11.25,
-            Imported::Yes,
-            Category::Food("box of chocolates".to_string()),
-        )
-        .unwrap();
-        let basket = Basket::new(vec![
-            imported_perfume,
-            perfume,
-            headache_pills,
-            imported_chocolates,
-        ]);
-        assert_relative_eq!(basket.get_total(), 74.63, epsilon = f64::EPSILON);
-        assert_relative_eq!(basket.get_tax(), 6.65, epsilon = f64::EPSILON);
+    fn test_purchase_3() {
+        let csv = include_str!("../tests/fixtures/purchase_3.csv");
+        let basket = Basket::from_csv(csv.as_bytes()).unwrap();
         assert_eq!(
             basket.to_string(),
             "1 imported bottle of perfume: 32.19
 1 bottle of perfume: 20.89
 1 packet of headache pills: 9.75
-1 imported box of chocolates: 11.80
-Sales Taxes: 6.65
-Total: 74.63"
+1 imported box of chocolates: 11.85
+Sales Taxes: 6.70
+Total: 74.68"
         );
     }
-}
-
-#[cfg(test)]
-mod string_to_basket_tests {
-    use super::*;
-    use approx::assert_relative_eq;
     #[test]
-    fn test_parse_basket() {
-        let input = "1 imported bottle of perfume at 27.99
-1 bottle of perfume at 18.99
-1 packet of headache pills at 9.75
-1 box of imported chocolates at 11.25";
-        let basket = Basket::<Item>::from_str(input).unwrap();
-        assert_eq!(basket.elements.len(), 4);
-        assert_relative_eq!(basket.get_total(), 74.63, epsilon = f64::EPSILON);
-        assert_relative_eq!(basket.get_tax(), 6.65, epsilon = f64::EPSILON);
+    fn test_invalid_record() {
+        let csv = "quantity,description,imported,price
+2,book,no,12.49
+1,music CD,maybe,14.99";
+        match Basket::from_csv(csv.as_bytes()).unwrap_err() {
+            CsvError::Item(error) => {
+                assert_eq!(error.line, 3);
+                assert_eq!(error.text, "1,music CD,maybe,14.99");
+                assert_eq!(error.source, ParseItemError::InvalidImported);
+            }
+            error => panic!("unexpected error: {}", error),
+        }
+        let csv = "quantity,description,imported,price
+1,book,no,abc";
+        match Basket::from_csv(csv.as_bytes()).unwrap_err() {
+            CsvError::Item(error) => assert_eq!(error.source, ParseItemError::InvalidPrice),
+            error => panic!("unexpected error: {}", error),
+        }
+    }
+    #[test]
+    fn test_smallest_quantity() {
+        let csv = "quantity,description,imported,price
+-2147483648,book,no,0.00";
+        assert!(Basket::from_csv(csv.as_bytes()).is_ok());
+    }
+    #[test]
+    fn test_uneven_record() {
+        let csv = "quantity,description,imported,price
+1,book";
+        assert!(matches!(
+            Basket::from_csv(csv.as_bytes()).unwrap_err(),
+            CsvError::Read(_)
+        ));
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod acceptance_tests {
     use super::*;
     #[test]
@@ -444,9 +6192,41 @@ Total: 65.15"
             "1 imported bottle of perfume: 32.19
 1 bottle of perfume: 20.89
 1 packet of headache pills: 9.75
-1 imported box of chocolates: 11.80
-Sales Taxes: 6.65
-Total: 74.63"
+1 imported box of chocolates: 11.85
+Sales Taxes: 6.70
+Total: 74.68"
         );
     }
 }
+
+// Only uses the numeric core, so it also runs with `--no-default-features --features libm`.
+#[cfg(test)]
+mod no_std_tests {
+    use super::*;
+
+    struct Line(Money);
+
+    impl Tax for Line {
+        fn get_prices(&self) -> (Money, Money) {
+            let tax = round_numbers(self.0, 0.15, Money::from_cents(5), RoundingStrategy::Ceil);
+            (self.0, tax)
+        }
+    }
+
+    #[test]
+    fn test_core_prices() {
+        let perfume: Money = "27.99".parse().unwrap();
+        let prices: Prices = [Line(perfume), Line(Money::from_cents(1000))]
+            .iter()
+            .map(Tax::get_prices)
+            .sum();
+        assert_eq!(prices.tax, Money::from_cents(420 + 150));
+        assert_eq!(prices.total(), Money::from_cents(2799 + 420 + 1000 + 150));
+    }
+
+    #[test]
+    fn test_money_from_str_with_one_decimal() {
+        assert_eq!("0.5".parse(), Ok(Money::from_cents(50)));
+        assert_eq!("-12".parse(), Ok(Money::from_cents(-1200)));
+    }
+}
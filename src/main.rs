@@ -1,13 +1,40 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
 use std::str::FromStr;
 
 use sales_taxes_kata::{Basket, Item};
 
-fn main() {
-    let input_1 = "1 imported bottle of perfume at 27.99
+const DEMO: &str = "1 imported bottle of perfume at 27.99
 1 bottle of perfume at 18.99
 1 packet of headache pills at 9.75
 1 box of imported chocolates at 11.25";
-    let basket_1 = Basket::<Item>::from_str(input_1).unwrap();
-    // println!("{:?}", basket_1);
-    println!("{}", basket_1.to_string());
+
+/// Reads the basket from the file given as first argument, or from stdin.
+/// `--demo` uses the third purchase of the kata instead.
+fn read_input() -> io::Result<String> {
+    match env::args().nth(1).as_deref() {
+        Some("--demo") => Ok(DEMO.to_string()),
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+fn main() {
+    let input = read_input().unwrap_or_else(|e| {
+        eprintln!("error: cannot read the basket: {}", e);
+        process::exit(1);
+    });
+    match Basket::<Item>::from_str(&input) {
+        Ok(basket) => println!("{}", basket),
+        Err(e) => {
+            eprintln!("error: invalid basket at {}", e);
+            process::exit(1);
+        }
+    }
 }